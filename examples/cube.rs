@@ -1,7 +1,6 @@
 use bevy::{
     log::{Level, LogPlugin},
     prelude::*,
-    render::camera::ScalingMode,
     window::WindowResolution,
 };
 use bevy_mod_fbx::FbxPlugin;
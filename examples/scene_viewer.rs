@@ -11,7 +11,6 @@ use bevy::{
     input::mouse::MouseMotion,
     log::{Level, LogPlugin},
     prelude::*,
-    window::close_on_esc,
 };
 use bevy_mod_fbx::FbxPlugin;
 
@@ -41,13 +40,14 @@ Controls:
     .add_plugins(
         DefaultPlugins
             .set(AssetPlugin {
-                asset_folder: std::env::var("CARGO_MANIFEST_DIR")
-                    .unwrap_or_else(|_| ".".to_string()),
-                watch_for_changes: true,
+                file_path: std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string()),
+                watch_for_changes_override: Some(true),
+                ..default()
             })
             .set(LogPlugin {
                 level: Level::WARN,
                 filter: "bevy_mod_fbx=info".to_owned(),
+                ..default()
             })
             .set(WindowPlugin {
                 primary_window: Some(Window {
@@ -57,11 +57,9 @@ Controls:
                 ..default()
             }),
     )
-    .add_plugin(FbxPlugin)
-    .add_startup_system(setup)
-    .add_system(update_lights)
-    .add_system(camera_controller)
-    .add_system(close_on_esc);
+    .add_plugins(FbxPlugin)
+    .add_systems(Startup, setup)
+    .add_systems(Update, (update_lights, camera_controller, close_on_esc));
 
     app.run();
 }
@@ -75,60 +73,48 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     }
     info!("Loading {}", scene_path);
     commands.spawn((
-        Camera3dBundle {
-            transform: Transform::from_xyz(10.0, 4.4, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
-            ..default()
-        },
+        Camera3d::default(),
+        Transform::from_xyz(10.0, 4.4, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
         CameraController::default(),
     ));
 
     info!("Spawning a directional light");
-    commands.spawn(DirectionalLightBundle {
-        directional_light: DirectionalLight {
-            illuminance: 20000.0,
-            shadows_enabled: false,
-            ..default()
-        },
+    commands.spawn(DirectionalLight {
+        illuminance: 20000.0,
+        shadows_enabled: false,
         ..default()
     });
     commands.spawn((
-        SceneBundle {
-            scene: asset_server.load(&scene_path),
-            ..default()
-        },
+        SceneRoot(asset_server.load(&scene_path)),
         Name::new(scene_path),
     ));
 }
 
-const SCALE_STEP: f32 = 0.1;
+fn close_on_esc(
+    mut commands: Commands,
+    focused_windows: Query<(Entity, &Window)>,
+    input: Res<ButtonInput<KeyCode>>,
+) {
+    for (window, focus) in focused_windows.iter() {
+        if focus.focused && input.just_pressed(KeyCode::Escape) {
+            commands.entity(window).despawn();
+        }
+    }
+}
 
 fn update_lights(
-    key_input: Res<Input<KeyCode>>,
+    key_input: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
     mut query: Query<(&mut Transform, &mut DirectionalLight)>,
     mut animate_directional_light: Local<bool>,
 ) {
-    let mut projection_adjustment = Vec3::ONE;
-    if key_input.just_pressed(KeyCode::Key5) {
-        projection_adjustment.x -= SCALE_STEP;
-    } else if key_input.just_pressed(KeyCode::Key6) {
-        projection_adjustment.x += SCALE_STEP;
-    } else if key_input.just_pressed(KeyCode::Key7) {
-        projection_adjustment.y -= SCALE_STEP;
-    } else if key_input.just_pressed(KeyCode::Key8) {
-        projection_adjustment.y += SCALE_STEP;
-    } else if key_input.just_pressed(KeyCode::Key9) {
-        projection_adjustment.z -= SCALE_STEP;
-    } else if key_input.just_pressed(KeyCode::Key0) {
-        projection_adjustment.z += SCALE_STEP;
-    }
     for (_, mut light) in query.iter_mut() {
-        if key_input.just_pressed(KeyCode::U) {
+        if key_input.just_pressed(KeyCode::KeyU) {
             light.shadows_enabled = !light.shadows_enabled;
         }
     }
 
-    if key_input.just_pressed(KeyCode::L) {
+    if key_input.just_pressed(KeyCode::KeyL) {
         *animate_directional_light = !*animate_directional_light;
     }
     if *animate_directional_light {
@@ -136,7 +122,7 @@ fn update_lights(
             transform.rotation = Quat::from_euler(
                 EulerRot::ZYX,
                 0.0,
-                time.elapsed_seconds() * TAU / 30.0,
+                time.elapsed_secs() * TAU / 30.0,
                 -TAU / 8.,
             );
         }
@@ -171,15 +157,15 @@ impl Default for CameraController {
             enabled: true,
             initialized: false,
             sensitivity: 0.5,
-            key_forward: KeyCode::W,
-            key_back: KeyCode::S,
-            key_left: KeyCode::A,
-            key_right: KeyCode::D,
-            key_up: KeyCode::E,
-            key_down: KeyCode::Q,
-            key_run: KeyCode::LShift,
+            key_forward: KeyCode::KeyW,
+            key_back: KeyCode::KeyS,
+            key_left: KeyCode::KeyA,
+            key_right: KeyCode::KeyD,
+            key_up: KeyCode::KeyE,
+            key_down: KeyCode::KeyQ,
+            key_run: KeyCode::ShiftLeft,
             mouse_key_enable_mouse: MouseButton::Left,
-            keyboard_key_enable_mouse: KeyCode::M,
+            keyboard_key_enable_mouse: KeyCode::KeyM,
             walk_speed: 5.0,
             run_speed: 15.0,
             friction: 0.5,
@@ -193,12 +179,12 @@ impl Default for CameraController {
 fn camera_controller(
     time: Res<Time>,
     mut mouse_events: EventReader<MouseMotion>,
-    mouse_button_input: Res<Input<MouseButton>>,
-    key_input: Res<Input<KeyCode>>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
     mut move_toggled: Local<bool>,
     mut query: Query<(&mut Transform, &mut CameraController), With<Camera>>,
 ) {
-    let dt = time.delta_seconds();
+    let dt = time.delta_secs();
 
     if let Ok((mut transform, mut options)) = query.get_single_mut() {
         if !options.initialized {
@@ -259,7 +245,7 @@ fn camera_controller(
         // Handle mouse input
         let mut mouse_delta = Vec2::ZERO;
         if mouse_button_input.pressed(options.mouse_key_enable_mouse) || *move_toggled {
-            for mouse_event in mouse_events.iter() {
+            for mouse_event in mouse_events.read() {
                 mouse_delta += mouse_event.delta;
             }
         }
@@ -0,0 +1,389 @@
+//! Translation of FBX animations into bevy [`AnimationClip`]s.
+//!
+//! Animations are stored in FBX as a tree of objects:
+//! - `AnimationStack`: a single animation (or "take"), we load each of them
+//!   as an individual `AnimationClip`.
+//! - `AnimationLayer`: the stack is made of layers blended together.
+//!   We only load the first (base) layer.
+//! - `AnimationCurveNode`: animates a single property of a model,
+//!   such as `Lcl Translation`.
+//! - `AnimationCurve`: keyframes of a single component of the property
+//!   the curve node animates, `d|X`, `d|Y` or `d|Z`.
+//!
+//! FBX transforms can't be directly translated into bevy's `Transform`
+//! (see the `fbx_transform` module), so we sample the FBX animation
+//! at each keyframe and compute the corresponding bevy `Transform`
+//! using the same method as for the non-animated transform.
+//! Large rotations are also sampled between keyframes, since bevy
+//! interpolates the resulting quaternions along the shortest path.
+use std::fmt::Debug;
+
+use anyhow::{anyhow, Context, Result};
+use bevy::{
+    animation::{animated_field, animation_curves::*, AnimationClip, AnimationTargetId},
+    math::{
+        curve::{ConstantCurve, Interval, UnevenSampleAutoCurve},
+        DVec3, StableInterpolate,
+    },
+    prelude::{debug, warn, Name, Transform},
+    reflect::{FromReflect, Reflectable, TypePath},
+    utils::HashMap,
+};
+use fbxcel_dom::v7400::object::{model::ModelHandle, ObjectHandle, ObjectId, TypedObjectHandle};
+
+use crate::{
+    data::FbxObject,
+    fbx_transform::{BasisChange, FbxNodeTransformInfo, FbxTransform, LocalTransform},
+    utils::fbx_extend::{child_f32_array, child_i32_array, child_i64_array, Loadable},
+};
+
+/// Number of FBX time units (`KTime`) in a second.
+const KTIME_PER_SECOND: f64 = 46_186_158_000.0;
+
+/// `KeyAttrFlags` bit set when a key is held constant until the next key.
+const INTERPOLATION_CONSTANT: i32 = 0x0000_0002;
+
+/// How long before the next key we sample constant keys, in seconds.
+///
+/// Bevy interpolates linearly between samples, so we add a sample right before
+/// the next key to emulate constant interpolation.
+const CONSTANT_HOLD_OFFSET: f32 = 1e-4;
+
+/// The largest change of a rotation curve between two samples, in degrees.
+///
+/// Bevy interpolates rotations along the shortest path, so keys more than
+/// 180° apart would play the wrong way round. Samples of the three axes
+/// at most 45° apart are less than 180° apart once combined.
+const MAX_ROTATION_STEP: f32 = 45.0;
+
+/// The most samples added between two keys, to bound the work done on
+/// malformed files.
+const MAX_SUBDIVISIONS: usize = 64;
+
+/// The keyframes of an `AnimationCurve`.
+///
+/// Cubic interpolation is not supported, such keys are linearly interpolated.
+struct Curve {
+    /// Key times in seconds, relative to the start of the animation stack.
+    times: Vec<f32>,
+    values: Vec<f32>,
+    /// Whether the key at the same index is held constant until the next key.
+    constant: Vec<bool>,
+}
+impl Curve {
+    fn from_object(curve: ObjectHandle, start: i64) -> Result<Self> {
+        let node = curve.node();
+        let times = child_i64_array(node, "KeyTime")?.unwrap_or_default();
+        let values = child_f32_array(node, "KeyValueFloat")?.unwrap_or_default();
+        if times.len() != values.len() {
+            return Err(anyhow!(
+                "mismatched length of curve {:?} buffers: times{} values{}",
+                curve.object_id(),
+                times.len(),
+                values.len(),
+            ));
+        }
+        // Key attributes are shared by consecutive keys, `KeyAttrRefCount`
+        // tells how many keys use each `KeyAttrFlags`.
+        let flags = child_i32_array(node, "KeyAttrFlags")?.unwrap_or_default();
+        let ref_counts = child_i32_array(node, "KeyAttrRefCount")?.unwrap_or_default();
+        let repeated_flags = flags
+            .iter()
+            .zip(ref_counts)
+            .map(|(flags, count)| {
+                let count = usize::try_from(*count).map_err(|_| {
+                    anyhow!(
+                        "invalid key attribute count {count} in curve {:?}",
+                        curve.object_id()
+                    )
+                })?;
+                let is_constant = flags & INTERPOLATION_CONSTANT != 0;
+                Ok(std::iter::repeat_n(is_constant, count))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // Counts are not trusted, extra keys are ignored.
+        let mut constant: Vec<_> = repeated_flags
+            .into_iter()
+            .flatten()
+            .take(times.len())
+            .collect();
+        constant.resize(times.len(), false);
+
+        let to_seconds = |time: &i64| ((time - start) as f64 / KTIME_PER_SECOND) as f32;
+        Ok(Curve {
+            times: times.iter().map(to_seconds).collect(),
+            values: values.to_vec(),
+            constant,
+        })
+    }
+    /// The times at which this curve should be sampled to be reproduced
+    /// by a linearly interpolated bevy curve.
+    ///
+    /// Keys whose values differ by more than `max_step` are subdivided.
+    fn sample_times(&self, max_step: f32) -> impl Iterator<Item = f32> + '_ {
+        let holds = self
+            .times
+            .windows(2)
+            .zip(&self.constant)
+            .filter_map(|(window, constant)| {
+                let hold_time = (window[1] - CONSTANT_HOLD_OFFSET).max(window[0]);
+                constant.then_some(hold_time)
+            });
+        let subdivisions = self
+            .times
+            .windows(2)
+            .zip(self.values.windows(2))
+            .zip(&self.constant)
+            .filter(|(_, constant)| !**constant)
+            .flat_map(move |((times, values), _)| {
+                let steps = ((values[1] - values[0]).abs() / max_step).ceil() as usize;
+                let steps = steps.min(MAX_SUBDIVISIONS);
+                (1..steps).map(move |i| times[0] + (times[1] - times[0]) * i as f32 / steps as f32)
+            });
+        self.times.iter().copied().chain(holds).chain(subdivisions)
+    }
+    fn sample(&self, time: f32) -> Option<f32> {
+        let next = self.times.partition_point(|t| *t <= time);
+        let (first, last) = (self.values.first()?, self.values.last()?);
+        if next == 0 {
+            return Some(*first);
+        } else if next == self.times.len() {
+            return Some(*last);
+        }
+        let prev = next - 1;
+        if self.constant[prev] {
+            return Some(self.values[prev]);
+        }
+        let (t0, t1) = (self.times[prev], self.times[next]);
+        let (v0, v1) = (self.values[prev], self.values[next]);
+        Some(v0 + (v1 - v0) * (time - t0) / (t1 - t0))
+    }
+}
+
+/// The `d|X`, `d|Y` and `d|Z` curves of a curve node.
+#[derive(Default)]
+struct CurveNode([Option<Curve>; 3]);
+impl CurveNode {
+    fn from_object(curve_node: ObjectHandle, start: i64) -> Result<Self> {
+        let mut curves = CurveNode::default();
+        for curve in curve_node.source_objects() {
+            let index = match curve.label() {
+                Some("d|X") => 0,
+                Some("d|Y") => 1,
+                Some("d|Z") => 2,
+                _ => continue,
+            };
+            let Some(curve) = curve.object_handle() else {
+                continue;
+            };
+            curves.0[index] = Some(Curve::from_object(curve, start)?);
+        }
+        Ok(curves)
+    }
+    fn times(&self, max_step: f32) -> impl Iterator<Item = f32> + '_ {
+        self.0
+            .iter()
+            .flatten()
+            .flat_map(move |curve| curve.sample_times(max_step))
+    }
+    /// Sample all curves at `time`, using `default` for missing curves.
+    fn sample(&self, time: f32, default: DVec3) -> DVec3 {
+        let sample = |i: usize| match self.0[i].as_ref().and_then(|c| c.sample(time)) {
+            Some(value) => value as f64,
+            None => default[i],
+        };
+        DVec3::new(sample(0), sample(1), sample(2))
+    }
+}
+
+/// The animated `Lcl` properties of a model.
+#[derive(Default)]
+struct ModelCurves {
+    translation: CurveNode,
+    rotation: CurveNode,
+    scaling: CurveNode,
+}
+
+/// The [`AnimationTargetId`] of a model, based on the names of its ancestors.
+///
+/// This must match the `AnimationTarget`s set when spawning the scene.
+pub(crate) fn target_id(model: ModelHandle) -> AnimationTargetId {
+    let mut path = vec![model_name(model)];
+    let mut current = model;
+    while let Some(parent) = current.parent_model() {
+        path.push(model_name(*parent));
+        current = *parent;
+    }
+    AnimationTargetId::from_names(path.iter().rev())
+}
+fn model_name(model: ModelHandle) -> Name {
+    Name::new(model.name().unwrap_or_default().to_owned())
+}
+
+/// The global FBX transform of `model`.
+fn global_transform(model: ModelHandle) -> Result<FbxTransform> {
+    let parent = model
+        .parent_model()
+        .map(|parent| global_transform(*parent))
+        .transpose()?;
+    let info = FbxNodeTransformInfo::from_object(*model)?;
+    Ok(FbxTransform::from_fbxtrans(&info, parent))
+}
+
+/// Bevy `Transform`s of `model` at each of the keyframes in `curves`,
+/// expressed in the `basis` baked into the scene.
+fn sample_transforms(
+    model: ModelHandle,
+    curves: &ModelCurves,
+    basis: BasisChange,
+) -> Result<(Vec<f32>, Vec<Transform>)> {
+    let info = FbxNodeTransformInfo::from_object(*model)?;
+    let parent = model
+        .parent_model()
+        .map(|parent| global_transform(*parent))
+        .transpose()?;
+    let parent_global = parent.map(|p| p.global);
+
+    let ModelCurves {
+        translation,
+        rotation,
+        scaling,
+    } = curves;
+    let mut times: Vec<_> = translation
+        .times(f32::INFINITY)
+        .chain(rotation.times(MAX_ROTATION_STEP))
+        .chain(scaling.times(f32::INFINITY))
+        .collect();
+    times.sort_by(f32::total_cmp);
+    times.dedup();
+
+    let default = info.local();
+    let transforms = times
+        .iter()
+        .map(|time| {
+            let local = LocalTransform {
+                translation: translation.sample(*time, default.translation),
+                rotation: rotation.sample(*time, default.rotation),
+                scaling: scaling.sample(*time, default.scaling),
+            };
+            let transform = FbxTransform::from_fbxtrans(&info.with_local(local), parent);
+            basis.transform(transform.as_local_transform(parent_global))
+        })
+        .collect();
+    Ok((times, transforms))
+}
+
+/// Add to `clip` a curve animating `property` of `target`.
+fn add_curve<P>(
+    clip: &mut AnimationClip,
+    target: AnimationTargetId,
+    property: P,
+    times: &[f32],
+    values: Vec<P::Property>,
+) -> Result<()>
+where
+    P: AnimatableProperty + Clone,
+    P::Property: StableInterpolate + FromReflect + Reflectable + TypePath + Debug + Clone,
+{
+    if let [value] = &values[..] {
+        let curve = ConstantCurve::new(Interval::EVERYWHERE, value.clone());
+        clip.add_curve_to_target(target, AnimatableCurve::new(property, curve));
+    } else {
+        let curve = UnevenSampleAutoCurve::new(times.iter().copied().zip(values))?;
+        clip.add_curve_to_target(target, AnimatableCurve::new(property, curve));
+    }
+    Ok(())
+}
+
+/// Load the `AnimationStack` `stack` as an [`AnimationClip`].
+///
+/// Only the models in `hierarchy` are animated, `basis` is the basis change
+/// baked into their transforms.
+pub(crate) fn load_clip(
+    stack: ObjectHandle,
+    hierarchy: &HashMap<ObjectId, FbxObject>,
+    basis: BasisChange,
+) -> Result<AnimationClip> {
+    let stack_props = stack.properties_by_native_typename("FbxAnimStack");
+    let start = i64::get_property(stack_props, "LocalStart").unwrap_or(0);
+
+    let mut layers = stack
+        .source_objects()
+        .filter_map(|obj| obj.object_handle())
+        .filter(|obj| obj.class() == "AnimLayer");
+    let Some(layer) = layers.next() else {
+        return Ok(AnimationClip::default());
+    };
+    if layers.next().is_some() {
+        warn!(
+            "Animation {:?} has several layers, only the first one is loaded",
+            stack.name()
+        );
+    }
+
+    let mut models: HashMap<ObjectId, (ModelHandle, ModelCurves)> = HashMap::new();
+    let curve_nodes = layer
+        .source_objects()
+        .filter_map(|obj| obj.object_handle())
+        .filter(|obj| obj.class() == "AnimCurveNode");
+    for curve_node in curve_nodes {
+        for target in curve_node.destination_objects() {
+            let Some(TypedObjectHandle::Model(model)) =
+                target.object_handle().map(|o| o.get_typed())
+            else {
+                continue;
+            };
+            if !hierarchy.contains_key(&model.object_id()) {
+                continue;
+            }
+            let (_, curves) = models
+                .entry(model.object_id())
+                .or_insert_with(|| (*model, ModelCurves::default()));
+            let property = match target.label() {
+                Some("Lcl Translation") => &mut curves.translation,
+                Some("Lcl Rotation") => &mut curves.rotation,
+                Some("Lcl Scaling") => &mut curves.scaling,
+                label => {
+                    debug!("Animation of {label:?} is not supported, skipping");
+                    continue;
+                }
+            };
+            *property = CurveNode::from_object(curve_node, start)?;
+        }
+    }
+
+    let mut clip = AnimationClip::default();
+    for (model, curves) in models.values() {
+        let (times, transforms) = sample_transforms(*model, curves, basis)
+            .with_context(|| format!("Failed to sample animation of {:?}", model.name()))?;
+        if transforms.is_empty() {
+            continue;
+        }
+        let target = target_id(*model);
+        let translations = transforms.iter().map(|t| t.translation).collect();
+        let rotations = transforms.iter().map(|t| t.rotation).collect();
+        let scales = transforms.iter().map(|t| t.scale).collect();
+        add_curve(
+            &mut clip,
+            target,
+            animated_field!(Transform::translation),
+            &times,
+            translations,
+        )?;
+        add_curve(
+            &mut clip,
+            target,
+            animated_field!(Transform::rotation),
+            &times,
+            rotations,
+        )?;
+        add_curve(
+            &mut clip,
+            target,
+            animated_field!(Transform::scale),
+            &times,
+            scales,
+        )?;
+    }
+    Ok(clip)
+}
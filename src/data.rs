@@ -1,9 +1,18 @@
+#[cfg(feature = "animation")]
+use bevy::animation::{
+    graph::{AnimationGraph, AnimationNodeIndex},
+    AnimationClip,
+};
+use bevy::prelude::{Component, Reflect, ReflectComponent, TypePath};
 use bevy::{
-    prelude::{Handle, Image, Mesh, StandardMaterial, Transform},
+    asset::{Asset, UntypedHandle},
+    color::Color,
+    math::DVec3,
+    pbr::{DirectionalLight, PointLight, SpotLight},
+    prelude::{Handle, Image, Mesh, Projection, Transform},
+    render::mesh::skinning::SkinnedMeshInverseBindposes,
     utils::HashMap,
-    asset::Asset
 };
-use bevy::prelude::TypePath;
 use fbxcel_dom::v7400::object::ObjectId;
 
 #[derive(Debug, Clone, Asset, TypePath)]
@@ -11,7 +20,91 @@ use fbxcel_dom::v7400::object::ObjectId;
 pub struct FbxMesh {
     pub name: Option<String>,
     pub bevy_mesh_handles: Vec<Handle<Mesh>>,
-    pub materials: Vec<Handle<StandardMaterial>>,
+    /// The material of each of `bevy_mesh_handles`, of the type of [`Material`]
+    /// created by the [`MaterialLoader`] that loaded it.
+    ///
+    /// [`Material`]: bevy::pbr::Material
+    /// [`MaterialLoader`]: crate::material_loader::MaterialLoader
+    pub materials: Vec<UntypedHandle>,
+    /// The skin deforming this mesh, if it is rigged.
+    pub skin: Option<FbxSkin>,
+    /// The names of the morph targets of this mesh, one per blend shape channel.
+    ///
+    /// The index in this list is the index of the target in [`MorphWeights`].
+    ///
+    /// [`MorphWeights`]: bevy::render::mesh::morph::MorphWeights
+    pub morph_target_names: Vec<String>,
+    /// The default weight of each morph target, from the `DeformPercent` of
+    /// the blend shape channels.
+    pub morph_weights: Vec<f32>,
+    /// The transform of the mesh entities relative to the node entity.
+    ///
+    /// This is the geometric transform of the node with
+    /// [`FbxGeometricTransform::ChildEntity`], the identity otherwise.
+    ///
+    /// [`FbxGeometricTransform::ChildEntity`]: crate::FbxGeometricTransform::ChildEntity
+    pub geometric_transform: Transform,
+}
+
+/// The skin deformer of a [`FbxMesh`].
+///
+/// The loader adds a [`SkinnedMesh`] to the mesh entities of the spawned scene,
+/// so you shouldn't need to use this directly.
+///
+/// [`SkinnedMesh`]: bevy::render::mesh::skinning::SkinnedMesh
+#[derive(Debug, Clone)]
+pub struct FbxSkin {
+    pub name: Option<String>,
+    /// The models used as joints by the skin.
+    ///
+    /// The position in this list is the joint index used in the
+    /// [`Mesh::ATTRIBUTE_JOINT_INDEX`] vertex attribute.
+    pub joints: Vec<ObjectId>,
+    pub inverse_bindposes: Handle<SkinnedMeshInverseBindposes>,
+}
+
+/// A camera of a FBX scene.
+///
+/// The loader spawns it as an inactive [`Camera3d`] entity, child of the
+/// entity of its node, set [`Camera::is_active`] to render with it.
+///
+/// [`Camera3d`]: bevy::core_pipeline::core_3d::Camera3d
+/// [`Camera::is_active`]: bevy::render::camera::Camera::is_active
+#[derive(Debug, Clone)]
+pub struct FbxCamera {
+    pub name: Option<String>,
+    /// The projection of the camera.
+    ///
+    /// Distances are in the units of the file, like the view space of the
+    /// camera entity, which inherits the scale of the scene.
+    pub projection: Projection,
+    /// The transform of the camera entity relative to its node entity.
+    ///
+    /// FBX cameras look towards +X of their node, bevy cameras towards -Z.
+    pub transform: Transform,
+}
+
+/// A light of a FBX scene.
+///
+/// The loader spawns it as a child entity of the entity of its node.
+#[derive(Debug, Clone)]
+pub struct FbxLight {
+    pub name: Option<String>,
+    pub light: FbxLightKind,
+    /// The transform of the light entity relative to its node entity.
+    ///
+    /// FBX lights shine towards -Y of their node, bevy lights towards -Z.
+    pub transform: Transform,
+}
+
+/// The bevy light component of a [`FbxLight`].
+///
+/// Area lights are approximated as point lights.
+#[derive(Debug, Clone)]
+pub enum FbxLightKind {
+    Point(PointLight),
+    Spot(SpotLight),
+    Directional(DirectionalLight),
 }
 
 /// The data loaded from a FBX scene.
@@ -30,11 +123,28 @@ pub struct FbxMesh {
 pub struct FbxScene {
     pub name: Option<String>,
     pub bevy_meshes: HashMap<Handle<Mesh>, String>,
-    pub materials: HashMap<String, Handle<StandardMaterial>>,
+    /// The materials of the scene, by label, see [`FbxMesh::materials`].
+    pub materials: HashMap<String, UntypedHandle>,
     pub textures: HashMap<String, Handle<Image>>,
     pub meshes: HashMap<ObjectId, Handle<FbxMesh>>,
+    /// The cameras of the scene, by the id of their node.
+    pub cameras: HashMap<ObjectId, FbxCamera>,
+    /// The lights of the scene, by the id of their node.
+    pub lights: HashMap<ObjectId, FbxLight>,
     pub hierarchy: HashMap<ObjectId, FbxObject>,
     pub roots: Vec<ObjectId>,
+    /// The animation clips of the scene, by label (for example `FbxAnimation@Walk`).
+    #[cfg(feature = "animation")]
+    pub animations: HashMap<String, Handle<AnimationClip>>,
+    /// An animation graph with a node for each of the [`animations`](Self::animations),
+    /// labeled `FbxAnimationGraph`, if the scene has animations.
+    ///
+    /// The root entity of the spawned scene has an `AnimationGraphHandle` to it.
+    #[cfg(feature = "animation")]
+    pub animation_graph: Option<Handle<AnimationGraph>>,
+    /// The node of each animation in the [`animation_graph`](Self::animation_graph), by label.
+    #[cfg(feature = "animation")]
+    pub animation_nodes: HashMap<String, AnimationNodeIndex>,
 }
 
 /// An FBX object in the scene tree.
@@ -43,13 +153,45 @@ pub struct FbxScene {
 #[derive(Default, Debug, Clone)]
 pub struct FbxObject {
     pub name: Option<String>,
+    /// The subclass of the FBX `Model`, see [`FbxNodeSubclass`].
+    pub subclass: String,
     pub transform: Transform,
+    /// The user-defined properties of this node, also added as a component
+    /// to its entity when there are any.
+    pub user_properties: FbxUserProperties,
     /// The children of this node.
     ///
     /// # Notes
     /// Not all [`ObjectId`] declared as child of an `FbxObject`
     /// are relevant to Bevy.
-    /// Meaning that you won't find the `ObjectId` in `hierarchy` or `meshes`
+    /// Meaning that you won't find the `ObjectId` in `hierarchy`, `meshes`,
+    /// `cameras` or `lights`
     /// `HashMap`s of the [`FbxScene`] structure.
     pub children: Vec<ObjectId>,
 }
+
+/// The subclass of the FBX `Model` a node entity of the spawned scene comes from,
+/// for example `Mesh`, `Null`, `LimbNode`, `Camera` or `Light`.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct FbxNodeSubclass(pub String);
+
+/// The user-defined properties of a FBX node, by name.
+///
+/// Those are the custom attributes added by artists in their DCC tool,
+/// for example to store gameplay metadata.
+#[derive(Component, Debug, Clone, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct FbxUserProperties(pub HashMap<String, FbxPropertyValue>);
+
+/// The value of a user-defined property of a FBX node.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub enum FbxPropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Vector(DVec3),
+    /// A `Color`, `ColorRGB` or `ColorAndAlpha` property, read as linear RGB.
+    Color(Color),
+}
@@ -1,24 +1,195 @@
-#[derive(Debug, Clone)]
+use std::path::PathBuf;
+
+use bevy::{asset::ReadAssetBytesError, image::TextureError};
+use fbxcel_dom::{any, fbxcel::low::FbxVersion};
+
+/// An error that occurred while loading a FBX file with the [`FbxLoader`].
+///
+/// Errors are wrapped in [`Context`](Self::Context) layers telling what was
+/// being loaded when they happened, [`without_context`](Self::without_context)
+/// returns the error they wrap.
+///
+/// [`FbxLoader`]: crate::FbxLoader
+#[derive(Debug)]
 pub enum FbxLoadingError {
-    IncorrectFileVersion,
-    UnknownError,
-    Other(String),
+    /// Failed to read the FBX file.
+    Io(std::io::Error),
+    /// The file is not a valid binary FBX file.
+    Parse(any::Error),
+    /// The file is valid, but its FBX version is not supported.
+    UnsupportedVersion { major: u32, minor: u32 },
+    /// A geometry mesh doesn't have a layer element required to load it.
+    MissingLayer {
+        mesh: String,
+        /// The kind of the missing layer element, such as `"Material"`.
+        layer: &'static str,
+    },
+    /// The polygons of a geometry mesh couldn't be triangulated.
+    Triangulation { mesh: String, source: anyhow::Error },
+    /// Failed to read the file of a texture.
+    TextureIo {
+        path: PathBuf,
+        source: ReadAssetBytesError,
+    },
+    /// Failed to decode the image of a texture.
+    ImageDecode { path: PathBuf, source: TextureError },
+    /// None of the [`MaterialLoader`]s could load a material.
+    ///
+    /// [`MaterialLoader`]: crate::material_loader::MaterialLoader
+    MaterialLoader { material: String },
+    /// Any other error, with the context in which it happened.
+    Other(anyhow::Error),
+    /// What was being loaded when the `source` error happened,
+    /// such as `"Failed to load material \"Skin\""`.
+    Context {
+        context: String,
+        source: Box<FbxLoadingError>,
+    },
 }
 
-impl std::error::Error for FbxLoadingError {}
+impl FbxLoadingError {
+    /// This error, without the [`Context`](Self::Context) in which it happened.
+    pub fn without_context(&self) -> &FbxLoadingError {
+        match self {
+            Self::Context { source, .. } => source.without_context(),
+            err => err,
+        }
+    }
+}
+
+impl std::error::Error for FbxLoadingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Parse(err) => Some(err),
+            Self::Triangulation { source, .. } => Some(source.as_ref()),
+            Self::TextureIo { source, .. } => Some(source),
+            Self::ImageDecode { source, .. } => Some(source),
+            Self::Other(err) => err.source(),
+            Self::Context { source, .. } => Some(source.as_ref()),
+            Self::UnsupportedVersion { .. }
+            | Self::MissingLayer { .. }
+            | Self::MaterialLoader { .. } => None,
+        }
+    }
+}
 
 impl std::fmt::Display for FbxLoadingError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::IncorrectFileVersion => write!(f, "Incorrect file version"),
-            Self::UnknownError => write!(f, "Unknown error"),
-            Self::Other(s) => write!(f, "{}", s),
+            Self::Io(_) => write!(f, "Failed to read FBX file"),
+            Self::Parse(_) => write!(f, "Failed to parse FBX document"),
+            Self::UnsupportedVersion { major, minor } => {
+                write!(
+                    f,
+                    "Unsupported FBX version {major}.{minor}, only 7.4 and 7.5 are supported"
+                )
+            }
+            Self::MissingLayer { mesh, layer } => {
+                write!(f, "Mesh {mesh:?} has no {layer} layer element")
+            }
+            Self::Triangulation { mesh, .. } => write!(f, "Failed to triangulate mesh {mesh:?}"),
+            Self::TextureIo { path, .. } => write!(f, "Failed to read texture file {path:?}"),
+            Self::ImageDecode { path, .. } => write!(f, "Failed to decode texture image {path:?}"),
+            Self::MaterialLoader { material } => {
+                write!(
+                    f,
+                    "None of the material loaders could load material {material:?}"
+                )
+            }
+            Self::Other(err) => write!(f, "{err}"),
+            Self::Context { context, .. } => write!(f, "{context}"),
         }
     }
 }
 
 impl From<std::io::Error> for FbxLoadingError {
     fn from(err: std::io::Error) -> Self {
-        Self::Other(err.to_string())
+        Self::Io(err)
+    }
+}
+
+impl From<any::Error> for FbxLoadingError {
+    fn from(err: any::Error) -> Self {
+        match err {
+            any::Error::UnsupportedVersion(version) => version.into(),
+            err => Self::Parse(err),
+        }
+    }
+}
+
+impl From<FbxVersion> for FbxLoadingError {
+    fn from(version: FbxVersion) -> Self {
+        let (major, minor) = version.major_minor();
+        Self::UnsupportedVersion { major, minor }
+    }
+}
+
+/// Recover the `FbxLoadingError` from an error chain, keeping the context
+/// added to it as [`FbxLoadingError::Context`] layers.
+impl From<anyhow::Error> for FbxLoadingError {
+    fn from(err: anyhow::Error) -> Self {
+        let context: Vec<_> = err
+            .chain()
+            .take_while(|err| !err.is::<FbxLoadingError>())
+            .map(ToString::to_string)
+            .collect();
+        match err.downcast::<FbxLoadingError>() {
+            Ok(err) => context
+                .into_iter()
+                .rev()
+                .fold(err, |source, context| Self::Context {
+                    context,
+                    source: Box::new(source),
+                }),
+            Err(err) => Self::Other(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::FbxLoadingError;
+
+    fn missing_layer() -> FbxLoadingError {
+        FbxLoadingError::MissingLayer {
+            mesh: "Body".to_owned(),
+            layer: "Material",
+        }
+    }
+
+    #[test]
+    fn context_is_kept() {
+        let err: anyhow::Result<()> = Err(missing_layer().into());
+        let err = err
+            .context("Failed to load mesh \"Body\"")
+            .context("Failed to load scene")
+            .unwrap_err();
+        let err = FbxLoadingError::from(err);
+
+        let chain: Vec<_> = anyhow::Chain::new(&err).map(ToString::to_string).collect();
+        assert_eq!(
+            chain,
+            [
+                "Failed to load scene",
+                "Failed to load mesh \"Body\"",
+                "Mesh \"Body\" has no Material layer element",
+            ]
+        );
+        assert!(matches!(
+            err.without_context(),
+            FbxLoadingError::MissingLayer {
+                layer: "Material",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn error_without_context_is_unchanged() {
+        let err = FbxLoadingError::from(anyhow::Error::from(missing_layer()));
+        assert!(matches!(err, FbxLoadingError::MissingLayer { .. }));
     }
 }
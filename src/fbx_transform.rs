@@ -21,7 +21,7 @@
 use std::f32::consts::TAU;
 
 use anyhow::Result;
-use bevy::math::{DVec3, EulerRot};
+use bevy::math::{DVec3, EulerRot, Mat3};
 use bevy::prelude::{Mat4, Transform, Vec3};
 use fbxcel_dom::v7400::object::{model::ModelHandle, property::ObjectProperties, ObjectHandle};
 
@@ -68,16 +68,27 @@ impl Scale {
 struct NodeScale {
     pivot: Translation,
     offset: Translation,
-    local: Scale,
 }
 #[derive(Clone, Debug)]
 struct NodeRotation {
     pivot: Translation,
     offset: Translation,
-    local: Rotation,
+    order: EulerRot,
     pre: Rotation,
     post: Rotation,
 }
+
+/// The `Lcl {Translation,Rotation,Scaling}` properties of a FBX node.
+///
+/// Those are the only transform properties that can be animated.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct LocalTransform {
+    pub(crate) translation: DVec3,
+    /// Euler angles in **degrees**, in the node's `RotationOrder`.
+    pub(crate) rotation: DVec3,
+    pub(crate) scaling: DVec3,
+}
+
 /// Handle the awkward translation from FBX to Bevy transform.
 ///
 /// The transform propagation in FBX is _way too flexible_,
@@ -87,18 +98,18 @@ struct NodeRotation {
 /// where we set the local transform infered
 /// from the computed FBX global position.
 #[derive(Clone, Debug)]
-struct FbxNodeTransformInfo {
+pub(crate) struct FbxNodeTransformInfo {
     rotation: NodeRotation,
-    translation: Translation,
     scale: NodeScale,
+    local: LocalTransform,
     inherit_type: InheritType,
 }
 impl FbxNodeTransformInfo {
     // if you were wondering: "Lcl" stands for "Local"
     // FIXME: Non-zero {Rotation,Scaling}{Pivot,Offset} is untested.
-    // TODO: Geometric{Translation,Scaling,Rotation}
-    // (see docs.autodesk.com and stackoverflow.com links at top of this file)
-    fn from_object(object: ObjectHandle) -> Result<Self> {
+    // Geometric{Translation,Scaling,Rotation} are not inherited by children,
+    // they are handled separately by `geometric_transform`.
+    pub(crate) fn from_object(object: ObjectHandle) -> Result<Self> {
         fn load<T: Loadable>(p: ObjectProperties, attribute: &str) -> Result<T> {
             T::get_property(p, attribute)
         }
@@ -108,19 +119,51 @@ impl FbxNodeTransformInfo {
             rotation: NodeRotation {
                 pivot: Translation::from_double(load(p, "RotationPivot")?),
                 offset: Translation::from_double(load(p, "RotationOffset")?),
-                local: Rotation::from_euler(e, load(p, "Lcl Rotation")?),
+                order: e,
                 pre: Rotation::from_euler(e, load(p, "PreRotation")?),
                 post: Rotation::from_euler(e, load(p, "PostRotation")?),
             },
-            translation: Translation::from_double(load(p, "Lcl Translation")?),
             scale: NodeScale {
                 pivot: Translation::from_double(load(p, "ScalingPivot")?),
                 offset: Translation::from_double(load(p, "ScalingOffset")?),
-                local: Scale::from_double(load(p, "Lcl Scaling")?),
+            },
+            local: LocalTransform {
+                translation: load(p, "Lcl Translation")?,
+                rotation: load(p, "Lcl Rotation")?,
+                scaling: load(p, "Lcl Scaling")?,
             },
             inherit_type: load(p, "InheritType")?,
         })
     }
+
+    /// The `Lcl` properties of this node, as stored in the FBX file.
+    #[cfg(feature = "animation")]
+    pub(crate) fn local(&self) -> LocalTransform {
+        self.local
+    }
+
+    /// The same node, but with `local` as `Lcl` properties.
+    #[cfg(feature = "animation")]
+    pub(crate) fn with_local(&self, local: LocalTransform) -> Self {
+        FbxNodeTransformInfo {
+            local,
+            ..self.clone()
+        }
+    }
+}
+
+/// The `Geometric{Translation,Rotation,Scaling}` properties of a FBX node.
+///
+/// They offset the geometry attached to the node (the "object-offset" of
+/// 3ds Max) but, unlike the other transform properties, the children of the
+/// node don't inherit them. The rotation is always in XYZ order.
+pub(crate) fn geometric_transform(object: ObjectHandle) -> Result<Mat4> {
+    let p = object.properties_by_native_typename("FbxNode");
+    let translation = Translation::from_double(DVec3::get_property(p, "GeometricTranslation")?);
+    let rotation =
+        Rotation::from_euler(EulerRot::XYZ, DVec3::get_property(p, "GeometricRotation")?);
+    let scale = Scale::from_double(DVec3::get_property(p, "GeometricScaling")?);
+    Ok(translation.mat() * rotation.mat() * scale.mat())
 }
 
 //#[derive(Copy, Clone, Debug)]
@@ -135,7 +178,7 @@ fn get_reverse_transform(mat: Mat4) -> (Mat4, Mat4, Mat4) {
     let (_, rotation, translation) = mat.to_scale_rotation_translation();
     let rotation = mat_q(rotation);
     let translation = mat_t(translation);
-    let shear_scale = mat * rotation.inverse() * translation.inverse();
+    let shear_scale = rotation.inverse() * translation.inverse() * mat;
     (shear_scale, rotation, translation)
 }
 
@@ -143,38 +186,37 @@ fn get_reverse_transform(mat: Mat4) -> (Mat4, Mat4, Mat4) {
 // the goal of this method is to get something working ASAP,
 // performance can wait.
 // I particularly dislike the amount of matrix inversion and multiplication this incures.
-fn global_transform(node: FbxNodeTransformInfo, parent: Option<FbxTransform>) -> Mat4 {
+fn global_transform(node: &FbxNodeTransformInfo, parent: Option<FbxTransform>) -> Mat4 {
     let mat_t = Mat4::from_translation;
-    let rot = node.rotation;
-    let scale = node.scale;
-    let rotation = rot.pre.mat() * rot.local.mat() * rot.post.mat();
+    let rot = &node.rotation;
+    let scale = &node.scale;
+    let local_rotation = Rotation::from_euler(rot.order, node.local.rotation);
+    let local_scale = Scale::from_double(node.local.scaling);
+    // Note that the post rotation is applied inverted, see the "Pivot Management"
+    // section of the FBX SDK documentation.
+    let rotation = rot.pre.mat() * local_rotation.mat() * rot.post.mat().inverse();
 
     let FbxTransform {
         global: parent_transform,
         local_scale: local_parent_scale,
     } = parent.unwrap_or_default();
 
-    let (parent_shear_scale, parent_rotation, parent_translation) =
-        get_reverse_transform(parent_transform);
+    let (parent_shear_scale, parent_rotation, _) = get_reverse_transform(parent_transform);
     let parent_nonlocal_scale = parent_shear_scale * local_parent_scale.mat().inverse();
 
     let inherited_rot_scale = match node.inherit_type {
-        InheritType::RrSs => parent_rotation * rotation * parent_shear_scale * scale.local.mat(),
-        InheritType::RSrs => parent_rotation * parent_shear_scale * rotation * scale.local.mat(),
-        InheritType::Rrs => parent_rotation * rotation * parent_nonlocal_scale * scale.local.mat(),
+        InheritType::RrSs => parent_rotation * rotation * parent_shear_scale * local_scale.mat(),
+        InheritType::RSrs => parent_rotation * parent_shear_scale * rotation * local_scale.mat(),
+        InheritType::Rrs => parent_rotation * rotation * parent_nonlocal_scale * local_scale.mat(),
     };
     let with_off_piv = |offset: Translation, pivot: Translation, transform| {
         offset.mat() * pivot.mat() * transform * pivot.mat().inverse()
     };
-    let translation = node.translation.mat()
+    let translation = Translation::from_double(node.local.translation).mat()
         * with_off_piv(rot.offset, rot.pivot, rotation)
-        * with_off_piv(scale.offset, scale.pivot, scale.local.mat());
+        * with_off_piv(scale.offset, scale.pivot, local_scale.mat());
     let translation = translation.to_scale_rotation_translation().2;
-    // NOTE: this is unlike the Autodesk resource provided on top, it seems
-    // we need to remove the scale component from the parent's global matrix
-    // we multiply the translation with. Absolutely no idea why, but it works.
-    let parent_non_scale_transform = parent_translation * parent_rotation;
-    let global_translation = parent_non_scale_transform.transform_vector3(translation);
+    let global_translation = parent_transform.transform_point3(translation);
     mat_t(global_translation) * inherited_rot_scale
 }
 
@@ -194,13 +236,16 @@ impl Default for FbxTransform {
     }
 }
 impl FbxTransform {
-    pub(crate) fn from_node(node: ModelHandle, parent: Option<FbxTransform>) -> Self {
-        let transform = FbxNodeTransformInfo::from_object(*node).unwrap();
-        FbxTransform::from_fbxtrans(transform, parent)
+    pub(crate) fn from_node(node: ModelHandle, parent: Option<FbxTransform>) -> Result<Self> {
+        let transform = FbxNodeTransformInfo::from_object(*node)?;
+        Ok(FbxTransform::from_fbxtrans(&transform, parent))
     }
-    fn from_fbxtrans(trans: FbxNodeTransformInfo, parent: Option<FbxTransform>) -> Self {
+    pub(crate) fn from_fbxtrans(
+        trans: &FbxNodeTransformInfo,
+        parent: Option<FbxTransform>,
+    ) -> Self {
         FbxTransform {
-            local_scale: trans.scale.local,
+            local_scale: Scale::from_double(trans.local.scaling),
             global: global_transform(trans, parent),
         }
     }
@@ -213,13 +258,280 @@ impl FbxTransform {
     // 4. We need to find the child(Transform):
     //    - from bevy's transform mat: child(GlobalTransform) = parent(GlobalTransform) * child(Transform)
     //    - We have: child(GlobalTransform) and parent(GlobalTransform)
-    //    - child(Transform) = child(GlobalTransform) * parent(GlobalTransform)¯¹
+    //    - child(Transform) = parent(GlobalTransform)¯¹ * child(GlobalTransform)
     pub(crate) fn as_local_transform(&self, parent: Option<Mat4>) -> Transform {
         let mat = if let Some(parent) = parent {
-            self.global * parent.inverse()
+            parent.inverse() * self.global
         } else {
             self.global
         };
         Transform::from_matrix(mat)
     }
 }
+
+/// Change of basis from the axis system of a FBX file into bevy's.
+///
+/// Bevy is Y up and right-handed, with models facing +Z (the camera
+/// looks towards -Z). FBX files declare which axis is up, which axis
+/// is front and which axis points right (the "coord" axis) in their
+/// `GlobalSettings`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BasisChange(Mat3);
+impl BasisChange {
+    pub(crate) const IDENTITY: Self = Self(Mat3::IDENTITY);
+
+    /// The basis change for a file with the given `up`, `front` and `coord` axes,
+    /// each an axis index (0 for X, 1 for Y and 2 for Z) and a sign.
+    ///
+    /// Returns `None` if several of the axes are the same.
+    pub(crate) fn new(up: (usize, f32), front: (usize, f32), coord: (usize, f32)) -> Option<Self> {
+        if up.0 == front.0 || up.0 == coord.0 || front.0 == coord.0 {
+            return None;
+        }
+        let axis = |(index, sign): (usize, f32)| Vec3::AXES[index] * sign;
+        // Each row maps the file's axis onto bevy's X, Y and Z.
+        let rows = Mat3::from_cols(axis(coord), axis(up), axis(front));
+        Some(Self(rows.transpose()))
+    }
+
+    /// The change of basis matrix.
+    pub(crate) fn mat(&self) -> Mat4 {
+        Mat4::from_mat3(self.0)
+    }
+
+    /// Whether the file is left-handed, in which case triangle winding
+    /// must be reversed when baking the change into vertices.
+    pub(crate) fn is_mirror(&self) -> bool {
+        self.0.determinant() < 0.0
+    }
+
+    /// Express a point or direction of the file in bevy's basis.
+    pub(crate) fn vector(&self, vector: Vec3) -> Vec3 {
+        self.0 * vector
+    }
+
+    /// Express a transform matrix of the file in bevy's basis.
+    pub(crate) fn matrix(&self, matrix: Mat4) -> Mat4 {
+        // The basis is orthonormal, so its inverse is its transpose.
+        self.mat() * matrix * Mat4::from_mat3(self.0.transpose())
+    }
+
+    /// Express a transform of the file in bevy's basis.
+    pub(crate) fn transform(&self, transform: Transform) -> Transform {
+        Transform::from_matrix(self.matrix(transform.compute_matrix()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::math::{DVec3, EulerRot};
+    use bevy::prelude::{Mat4, Quat, Vec3};
+
+    use super::{
+        get_reverse_transform, FbxNodeTransformInfo, FbxTransform, LocalTransform, NodeRotation,
+        NodeScale, Rotation, Translation,
+    };
+    use crate::utils::fbx_extend::InheritType;
+
+    /// The transform properties of a FBX node, in XYZ rotation order.
+    struct Node {
+        translation: DVec3,
+        rotation: DVec3,
+        scaling: DVec3,
+        pre_rotation: DVec3,
+        post_rotation: DVec3,
+        rotation_pivot: DVec3,
+        rotation_offset: DVec3,
+        scaling_pivot: DVec3,
+    }
+    impl Default for Node {
+        fn default() -> Self {
+            Node {
+                translation: DVec3::ZERO,
+                rotation: DVec3::ZERO,
+                scaling: DVec3::ONE,
+                pre_rotation: DVec3::ZERO,
+                post_rotation: DVec3::ZERO,
+                rotation_pivot: DVec3::ZERO,
+                rotation_offset: DVec3::ZERO,
+                scaling_pivot: DVec3::ZERO,
+            }
+        }
+    }
+    impl Node {
+        fn global(&self, parent: Option<FbxTransform>) -> FbxTransform {
+            let order = EulerRot::XYZ;
+            let info = FbxNodeTransformInfo {
+                rotation: NodeRotation {
+                    pivot: Translation::from_double(self.rotation_pivot),
+                    offset: Translation::from_double(self.rotation_offset),
+                    order,
+                    pre: Rotation::from_euler(order, self.pre_rotation),
+                    post: Rotation::from_euler(order, self.post_rotation),
+                },
+                scale: NodeScale {
+                    pivot: Translation::from_double(self.scaling_pivot),
+                    offset: Translation::from_double(DVec3::ZERO),
+                },
+                local: LocalTransform {
+                    translation: self.translation,
+                    rotation: self.rotation,
+                    scaling: self.scaling,
+                },
+                inherit_type: InheritType::RrSs,
+            };
+            FbxTransform::from_fbxtrans(&info, parent)
+        }
+    }
+
+    /// A matrix from its columns, as the FBX SDK would compute it.
+    fn matrix(x: [f32; 3], y: [f32; 3], z: [f32; 3], w: [f32; 3]) -> Mat4 {
+        Mat4::from_cols(
+            Vec3::from(x).extend(0.0),
+            Vec3::from(y).extend(0.0),
+            Vec3::from(z).extend(0.0),
+            Vec3::from(w).extend(1.0),
+        )
+    }
+
+    fn assert_mat_eq(actual: Mat4, expected: Mat4) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-5),
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn pre_rotation_is_applied_before_local_rotation() {
+        // A Maya joint with a joint orient of 90° around Z, rotated 90° around X.
+        let node = Node {
+            pre_rotation: DVec3::new(0.0, 0.0, 90.0),
+            rotation: DVec3::new(90.0, 0.0, 0.0),
+            ..Node::default()
+        };
+        let expected = matrix([0., 1., 0.], [0., 0., 1.], [1., 0., 0.], [0., 0., 0.]);
+        assert_mat_eq(node.global(None).global, expected);
+    }
+
+    #[test]
+    fn post_rotation_is_inverted() {
+        let node = Node {
+            post_rotation: DVec3::new(0.0, 0.0, 90.0),
+            ..Node::default()
+        };
+        let expected = matrix([0., -1., 0.], [1., 0., 0.], [0., 0., 1.], [0., 0., 0.]);
+        assert_mat_eq(node.global(None).global, expected);
+    }
+
+    #[test]
+    fn equal_pre_and_post_rotations_cancel_out() {
+        let node = Node {
+            pre_rotation: DVec3::new(30.0, 45.0, 60.0),
+            post_rotation: DVec3::new(30.0, 45.0, 60.0),
+            ..Node::default()
+        };
+        assert_mat_eq(node.global(None).global, Mat4::IDENTITY);
+    }
+
+    #[test]
+    fn rotation_around_pivot() {
+        let node = Node {
+            rotation: DVec3::new(0.0, 0.0, 90.0),
+            rotation_pivot: DVec3::new(1.0, 0.0, 0.0),
+            ..Node::default()
+        };
+        let expected = matrix([0., 1., 0.], [-1., 0., 0.], [0., 0., 1.], [1., -1., 0.]);
+        assert_mat_eq(node.global(None).global, expected);
+    }
+
+    #[test]
+    fn scaling_around_pivot() {
+        let node = Node {
+            scaling: DVec3::splat(2.0),
+            scaling_pivot: DVec3::new(1.0, 0.0, 0.0),
+            ..Node::default()
+        };
+        let expected = matrix([2., 0., 0.], [0., 2., 0.], [0., 0., 2.], [-1., 0., 0.]);
+        assert_mat_eq(node.global(None).global, expected);
+    }
+
+    #[test]
+    fn rotation_offset_adds_to_translation() {
+        let node = Node {
+            translation: DVec3::new(1.0, 2.0, 3.0),
+            rotation: DVec3::new(0.0, 0.0, 90.0),
+            rotation_offset: DVec3::new(0.0, 1.0, 0.0),
+            ..Node::default()
+        };
+        let expected = matrix([0., 1., 0.], [-1., 0., 0.], [0., 0., 1.], [1., 3., 3.]);
+        assert_mat_eq(node.global(None).global, expected);
+    }
+
+    #[test]
+    fn child_is_moved_by_parent_translation_and_rotation() {
+        let parent = Node {
+            translation: DVec3::new(1.0, 0.0, 0.0),
+            rotation: DVec3::new(0.0, 0.0, 90.0),
+            ..Node::default()
+        };
+        let child = Node {
+            translation: DVec3::new(1.0, 0.0, 0.0),
+            ..Node::default()
+        };
+        let global = child.global(Some(parent.global(None))).global;
+        let expected = matrix([0., 1., 0.], [-1., 0., 0.], [0., 0., 1.], [1., 1., 0.]);
+        assert_mat_eq(global, expected);
+    }
+
+    #[test]
+    fn child_translation_is_scaled_by_parent() {
+        let parent = Node {
+            scaling: DVec3::splat(2.0),
+            ..Node::default()
+        };
+        let child = Node {
+            translation: DVec3::new(1.0, 0.0, 0.0),
+            ..Node::default()
+        };
+        let global = child.global(Some(parent.global(None))).global;
+        let expected = matrix([2., 0., 0.], [0., 2., 0.], [0., 0., 2.], [2., 0., 0.]);
+        assert_mat_eq(global, expected);
+    }
+
+    #[test]
+    fn local_transform_is_relative_to_parent() {
+        let parent = Node {
+            translation: DVec3::new(1.0, 0.0, 0.0),
+            rotation: DVec3::new(0.0, 0.0, 90.0),
+            scaling: DVec3::splat(2.0),
+            ..Node::default()
+        };
+        let child = Node {
+            translation: DVec3::new(1.0, 0.0, 0.0),
+            rotation: DVec3::new(0.0, 0.0, 90.0),
+            ..Node::default()
+        };
+        let parent = parent.global(None);
+        let local = child
+            .global(Some(parent))
+            .as_local_transform(Some(parent.global));
+        assert!(local.translation.abs_diff_eq(Vec3::X, 1e-5), "{local:?}");
+        let rotation = Quat::from_rotation_z(FRAC_PI_2);
+        assert!(local.rotation.abs_diff_eq(rotation, 1e-5), "{local:?}");
+        assert!(local.scale.abs_diff_eq(Vec3::ONE, 1e-5), "{local:?}");
+    }
+
+    #[test]
+    fn reverse_transform_splits_translation_rotation_and_scale() {
+        let translation = Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0));
+        let rotation = Mat4::from_rotation_z(FRAC_PI_2);
+        let scale = Mat4::from_scale(Vec3::new(2.0, 3.0, 4.0));
+        let (shear_scale, actual_rotation, actual_translation) =
+            get_reverse_transform(translation * rotation * scale);
+        assert_mat_eq(shear_scale, scale);
+        assert_mat_eq(actual_rotation, rotation);
+        assert_mat_eq(actual_translation, translation);
+    }
+}
@@ -1,16 +1,32 @@
+use std::sync::Arc;
+
 use bevy::prelude::{App, AssetApp, Plugin, Resource};
 
-pub use data::{FbxMesh, FbxScene};
-pub use loader::FbxLoader;
+pub use data::{
+    FbxCamera, FbxLight, FbxLightKind, FbxMesh, FbxNodeSubclass, FbxObject, FbxPropertyValue,
+    FbxScene, FbxUserProperties,
+};
+pub use error::FbxLoadingError;
+/// The FBX parser used by the loader, whose types appear in the
+/// [`MaterialLoader`] and [`FbxNodeProcessor`] APIs.
+pub use fbxcel_dom;
+pub use loader::{
+    FbxAxisConversion, FbxColorSpace, FbxGeometricTransform, FbxLightIntensity, FbxLoader,
+    FbxLoaderSettings, FbxNormalGeneration,
+};
 
+#[cfg(feature = "animation")]
+pub(crate) mod animation;
 pub(crate) mod data;
 pub(crate) mod fbx_transform;
 pub(crate) mod loader;
 pub mod material_loader;
+pub mod node_processor;
 pub(crate) mod utils;
 pub(crate) mod error;
 
 use material_loader::MaterialLoader;
+use node_processor::FbxNodeProcessor;
 
 /// Adds support for FBX file loading to the app.
 #[derive(Default)]
@@ -28,17 +44,27 @@ pub struct FbxPlugin;
 ///
 /// The default loaders are defined by [`material_loader::default_loader_order`].
 #[derive(Clone, Resource)]
-pub struct FbxMaterialLoaders(pub Vec<MaterialLoader>);
+pub struct FbxMaterialLoaders(pub Vec<Arc<dyn MaterialLoader>>);
 impl Default for FbxMaterialLoaders {
     fn default() -> Self {
-        Self(material_loader::default_loader_order().into())
+        Self(material_loader::default_loader_order())
     }
 }
 
+/// Resource to add [`FbxNodeProcessor`]s to the `FbxLoader`, which
+/// are called for each node of the scenes it spawns.
+///
+/// Like [`FbxMaterialLoaders`], insert it **before** adding the `FbxPlugin`
+/// to the app. There are none by default.
+#[derive(Clone, Default, Resource)]
+pub struct FbxNodeProcessors(pub Vec<Arc<dyn FbxNodeProcessor>>);
+
 impl Plugin for FbxPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset_loader::<FbxLoader>()
             .init_asset::<FbxMesh>()
-            .init_asset::<FbxScene>();
+            .init_asset::<FbxScene>()
+            .register_type::<FbxNodeSubclass>()
+            .register_type::<FbxUserProperties>();
     }
 }
@@ -1,36 +1,46 @@
-use std::path::Path;
+use std::{any::TypeId, f32::consts::FRAC_PI_2, path::Path, sync::Arc};
 
 use anyhow::{anyhow, bail, Context};
 
 use bevy::{
     asset::{
-        io::Reader,
-        AssetLoader, LoadContext, RenderAssetUsages,
+        io::Reader, AssetId, AssetLoader, LoadContext, RenderAssetUsages, UntypedAssetId,
+        UntypedHandle,
     },
-    math::{DVec2, DVec3, Vec2, Vec3},
+    color::{Color, ColorToComponents, LinearRgba, Srgba},
+    core_pipeline::core_3d::Camera3d,
+    image::{CompressedImageFormats, ImageSampler, ImageSamplerDescriptor, ImageType},
+    math::{DVec2, DVec3, Mat3, Mat4, Vec2, Vec3},
+    pbr::{DirectionalLight, PointLight, SpotLight, UvChannel, MAX_JOINTS},
     prelude::{
-        BuildChildren, ChildBuild, debug, error, info, trace,
-        FromWorld, Handle, Image, Mesh, Mesh3d, Name,
-        MeshMaterial3d, Scene, StandardMaterial, Transform, 
-        Visibility, World, WorldChildBuilder,
+        debug, error, info, trace, warn, BuildChildren, ChildBuild, Entity, FromWorld, Handle,
+        Image, Mesh, Mesh3d, Name, Scene, StandardMaterial, Transform, Visibility, World,
+        WorldChildBuilder,
     },
     render::{
-        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
-        render_resource::AddressMode,
+        camera::{Camera, OrthographicProjection, PerspectiveProjection, Projection, ScalingMode},
+        mesh::{
+            morph::{
+                MeshMorphWeights, MorphAttributes, MorphTargetImage, MorphWeights,
+                MAX_MORPH_WEIGHTS,
+            },
+            skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+            MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues,
+        },
+        render_resource::{AddressMode, VertexFormat},
         renderer::RenderDevice,
     },
-    image::{
-        CompressedImageFormats, ImageSampler, ImageType, 
-        ImageSamplerDescriptor,
-    },
-    utils::{ConditionalSendFuture, HashMap},
+    utils::{ConditionalSendFuture, HashMap, HashSet},
 };
 
+use rgb::RGB;
+use serde::{Deserialize, Serialize};
+
 use fbxcel_dom::{
     any::AnyDocument,
     v7400::{
         data::{
-            mesh::layer::TypedLayerElementHandle,
+            mesh::{layer::TypedLayerElementHandle, ControlPointIndex},
             texture::WrapMode,
         },
         object::{
@@ -46,31 +56,247 @@ use fbxcel_dom::{
 #[cfg(feature = "profile")]
 use bevy::log::info_span;
 
+#[cfg(feature = "animation")]
+use bevy::animation::{
+    graph::{AnimationGraph, AnimationGraphHandle},
+    AnimationClip, AnimationPlayer, AnimationTarget, AnimationTargetId,
+};
+
+#[cfg(feature = "animation")]
+use crate::animation;
+
 use crate::{
-    data::{FbxMesh, FbxObject, FbxScene},
+    data::{
+        FbxCamera, FbxLight, FbxLightKind, FbxMesh, FbxNodeSubclass, FbxObject, FbxScene, FbxSkin,
+    },
     error::FbxLoadingError,
-    fbx_transform::FbxTransform,
+    fbx_transform::{geometric_transform, BasisChange, FbxTransform},
+    material_loader::{insert_material, InsertMaterial, LoadedMaterial, MaterialLoader},
+    node_processor::FbxNodeProcessor,
     utils::{
-        fbx_extend::{GlobalSettingsExt, ModelTreeRootExt},
-        triangulate,
+        fbx_extend::{
+            polygon_vertex_indices, property_color, user_properties, BlendShapeChannelHandleExt,
+            ClusterHandleExt, GlobalSettingsExt, Loadable, MeshHandleExt, ModelTreeRootExt,
+            NodeAttributeExt, RawLayerElement, ShapeHandleExt, SkinJointsExt,
+        },
+        normals::{self, Polygons, Smoothing},
+        triangulate, vertices,
     },
-    MaterialLoader,
 };
 
 /// Bevy is kinda "meters" based while FBX (or rather: stuff exported by maya) is in "centimeters"
 /// Although it doesn't mean much in practice.
 const FBX_TO_BEVY_SCALE_FACTOR: f32 = 0.01;
 
+/// Maximum number of joints influencing a single vertex, imposed by bevy's
+/// `ATTRIBUTE_JOINT_INDEX` and `ATTRIBUTE_JOINT_WEIGHT` formats.
+const MAX_INFLUENCES: usize = 4;
+
+/// Per-control point joint indices and weights of a skinned mesh.
+struct ControlPointSkin {
+    joint_indices: Vec<[u16; MAX_INFLUENCES]>,
+    joint_weights: Vec<[f32; MAX_INFLUENCES]>,
+}
+
+/// The morph targets of a mesh, with per-control point offsets.
+struct ControlPointMorphs {
+    names: Vec<String>,
+    /// The default weight of each morph target.
+    weights: Vec<f32>,
+    /// For each morph target, the position and normal offset of each control point.
+    targets: Vec<Vec<(Vec3, Vec3)>>,
+}
+
 pub struct Loader<'b, 'w> {
     scene: FbxScene,
     load_context: &'b mut LoadContext<'w>,
     suported_compressed_formats: CompressedImageFormats,
-    material_loaders: Vec<MaterialLoader>,
+    material_loaders: Vec<Arc<dyn MaterialLoader>>,
+    node_processors: Vec<Arc<dyn FbxNodeProcessor>>,
+    settings: &'b FbxLoaderSettings,
+    /// The basis change baked into the loaded vertices and transforms.
+    basis: BasisChange,
+    /// The loaded materials with a normal map, whose meshes need tangents.
+    normal_mapped_materials: HashSet<UntypedAssetId>,
+    /// How to add the materials of each loaded material type to their mesh entities.
+    material_inserts: HashMap<TypeId, InsertMaterial>,
+}
+
+/// Per-asset settings of the [`FbxLoader`].
+///
+/// Set them with [`AssetServer::load_with_settings`] or in the `.fbx.meta` file
+/// of the asset.
+///
+/// [`AssetServer::load_with_settings`]: bevy::asset::AssetServer::load_with_settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FbxLoaderSettings {
+    /// Scale of the spawned scene root.
+    ///
+    /// When `None`, the scale is derived from the `UnitScaleFactor` of the file,
+    /// so that one FBX centimeter is one hundredth of a bevy unit.
+    pub unit_scale: Option<f32>,
+    /// Whether to load meshes.
+    ///
+    /// When `false`, mesh nodes are spawned as plain entities, and their
+    /// materials aren't loaded either.
+    pub load_meshes: bool,
+    /// Whether to load materials, meshes use the default material otherwise.
+    pub load_materials: bool,
+    /// Whether to load the textures of materials.
+    pub load_textures: bool,
+    /// Whether to load animation stacks as `AnimationClip`s.
+    ///
+    /// This has no effect without the `animation` cargo feature.
+    pub load_animations: bool,
+    /// Whether to only spawn the nodes with meshes, cameras, lights or skin joints
+    /// below them, instead of all the nodes of the file.
+    pub prune_empty_nodes: bool,
+    /// Whether to load cameras, spawned as inactive [`Camera3d`] entities.
+    pub load_cameras: bool,
+    /// Whether to load lights.
+    pub load_lights: bool,
+    /// How to convert the intensity of lights into bevy's units.
+    pub light_intensity: FbxLightIntensity,
+    /// Where the loaded meshes, morph targets and textures are kept.
+    pub asset_usage: RenderAssetUsages,
+    /// Whether to compute tangents for normal mapped meshes that don't have
+    /// tangents and binormals in the file.
+    pub generate_tangents: bool,
+    /// How to convert the axis system of the file into bevy's.
+    pub axis_conversion: FbxAxisConversion,
+    /// The color space of the vertex colors stored in the file.
+    pub vertex_color_space: FbxColorSpace,
+    /// How to compute the normals of meshes that don't have normals in the file.
+    pub normal_generation: FbxNormalGeneration,
+    /// How to apply the geometric transform of mesh nodes.
+    pub geometric_transform: FbxGeometricTransform,
+}
+
+/// How the [`FbxLoader`] converts the axis system declared in a file
+/// (for example Z up for 3ds Max) into bevy's Y up, right-handed, -Z forward.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FbxAxisConversion {
+    /// Keep the axes of the file.
+    None,
+    /// Rotate the root entity of the spawned scene.
+    #[default]
+    RootTransform,
+    /// Convert vertices, node transforms, bind poses and animations,
+    /// so that the root entity of the spawned scene is only scaled.
+    Bake,
+}
+
+/// How the [`FbxLoader`] computes the normals of meshes without normals.
+///
+/// The smoothing groups and hard edges of the file are respected.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FbxNormalGeneration {
+    /// Each polygon is flat shaded.
+    Flat,
+    /// Neighbouring polygons are smoothed together, unless they make an angle
+    /// greater than `max_angle` degrees or are separated by a hard edge.
+    Smooth { max_angle: f32 },
+}
+impl Default for FbxNormalGeneration {
+    fn default() -> Self {
+        Self::Smooth { max_angle: 60.0 }
+    }
+}
+
+/// How the [`FbxLoader`] applies the `Geometric{Translation,Rotation,Scaling}`
+/// of mesh nodes, which offset their mesh without affecting their children.
+///
+/// Skinned meshes always have it baked into their inverse bind poses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FbxGeometricTransform {
+    /// Transform the vertices of the mesh.
+    #[default]
+    Bake,
+    /// Set it as the [`Transform`] of the mesh entities, which are children
+    /// of the node entity.
+    ChildEntity,
+}
+
+/// How the [`FbxLoader`] converts the `Intensity` of FBX lights into bevy's
+/// photometric units.
+///
+/// FBX intensities are percentages, 100 for an intensity of 1 in most DCC tools,
+/// which don't agree on its physical meaning.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FbxLightIntensity {
+    /// Luminous power in lumens of point, spot and area lights of intensity 100.
+    pub lumens: f32,
+    /// Illuminance in lux of directional lights of intensity 100.
+    pub lux: f32,
+}
+impl Default for FbxLightIntensity {
+    /// The intensities of bevy's default lights.
+    fn default() -> Self {
+        Self {
+            lumens: PointLight::default().intensity,
+            lux: DirectionalLight::default().illuminance,
+        }
+    }
+}
+
+/// The color space of colors stored in a FBX file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FbxColorSpace {
+    /// Non-linear sRGB, as painted in most DCC tools.
+    #[default]
+    Srgb,
+    /// Linear RGB, as used by bevy.
+    Linear,
+}
+impl FbxColorSpace {
+    fn to_linear(self, [r, g, b, a]: [f64; 4]) -> [f32; 4] {
+        let color = [r as f32, g as f32, b as f32, a as f32];
+        match self {
+            Self::Srgb => LinearRgba::from(Srgba::from_f32_array(color)).to_f32_array(),
+            Self::Linear => color,
+        }
+    }
+}
+
+impl Default for FbxLoaderSettings {
+    fn default() -> Self {
+        Self {
+            unit_scale: None,
+            load_meshes: true,
+            load_materials: true,
+            load_textures: true,
+            load_animations: true,
+            prune_empty_nodes: false,
+            load_cameras: true,
+            load_lights: true,
+            light_intensity: FbxLightIntensity::default(),
+            asset_usage: RenderAssetUsages::default(),
+            generate_tangents: true,
+            axis_conversion: FbxAxisConversion::default(),
+            vertex_color_space: FbxColorSpace::default(),
+            normal_generation: FbxNormalGeneration::default(),
+            geometric_transform: FbxGeometricTransform::default(),
+        }
+    }
 }
 
 pub struct FbxLoader {
     supported: CompressedImageFormats,
-    material_loaders: Vec<MaterialLoader>,
+    material_loaders: Vec<Arc<dyn MaterialLoader>>,
+    node_processors: Vec<Arc<dyn FbxNodeProcessor>>,
+}
+impl FbxLoader {
+    /// The second vertex color set of a mesh, the first one is [`Mesh::ATTRIBUTE_COLOR`].
+    ///
+    /// Custom materials can read it at location `Vertex_Color_1`.
+    pub const ATTRIBUTE_COLOR_1: MeshVertexAttribute =
+        MeshVertexAttribute::new("Vertex_Color_1", 3_284_110_721, VertexFormat::Float32x4);
+    /// The third vertex color set of a mesh.
+    pub const ATTRIBUTE_COLOR_2: MeshVertexAttribute =
+        MeshVertexAttribute::new("Vertex_Color_2", 3_284_110_722, VertexFormat::Float32x4);
+    /// The fourth vertex color set of a mesh.
+    pub const ATTRIBUTE_COLOR_3: MeshVertexAttribute =
+        MeshVertexAttribute::new("Vertex_Color_3", 3_284_110_723, VertexFormat::Float32x4);
 }
 impl FromWorld for FbxLoader {
     fn from_world(world: &mut World) -> Self {
@@ -80,22 +306,25 @@ impl FromWorld for FbxLoader {
             None => CompressedImageFormats::all(),
         };
         let loaders: crate::FbxMaterialLoaders = world.get_resource().cloned().unwrap_or_default();
+        let processors: crate::FbxNodeProcessors =
+            world.get_resource().cloned().unwrap_or_default();
         Self {
             supported,
             material_loaders: loaders.0,
+            node_processors: processors.0,
         }
     }
 }
 
 impl AssetLoader for FbxLoader {
     type Asset = FbxScene;
-    type Settings = ();
+    type Settings = FbxLoaderSettings;
     type Error = FbxLoadingError;
 
     fn load(
         &self,
         reader: &mut dyn Reader,
-        _settings: &Self::Settings,
+        settings: &Self::Settings,
         load_context: &mut LoadContext,
     ) -> impl ConditionalSendFuture<Output = Result<Self::Asset, Self::Error>> {
         Box::pin(async move {
@@ -103,20 +332,24 @@ impl AssetLoader for FbxLoader {
             reader.read_to_end(&mut bytes).await?;
             let cursor = std::io::Cursor::new(bytes.as_slice());
             let reader = std::io::BufReader::new(cursor);
-            let maybe_doc =
-                AnyDocument::from_seekable_reader(reader).expect("Failed to load document");
+            let maybe_doc = AnyDocument::from_seekable_reader(reader)?;
             if let AnyDocument::V7400(_ver, doc) = maybe_doc {
-                let loader =
-                    Loader::new(self.supported, self.material_loaders.clone(), load_context);
+                let loader = Loader::new(
+                    self.supported,
+                    self.material_loaders.clone(),
+                    self.node_processors.clone(),
+                    settings,
+                    load_context,
+                );
                 match loader.load(*doc).await {
                     Ok(scene) => Ok(scene),
                     Err(err) => {
                         error!("{err:?}");
-                        Err(FbxLoadingError::Other(err.to_string()))
+                        Err(err.into())
                     }
                 }
             } else {
-                Err(FbxLoadingError::IncorrectFileVersion)
+                Err(maybe_doc.fbx_version().into())
             }
         })
     }
@@ -125,73 +358,228 @@ impl AssetLoader for FbxLoader {
     }
 }
 
+/// The loaded FBX nodes, and what is attached to them, to spawn in a [`Scene`].
+struct SceneContents<'a> {
+    doc: &'a Document,
+    hierarchy: &'a HashMap<ObjectId, FbxObject>,
+    meshes: &'a HashMap<ObjectId, FbxMesh>,
+    cameras: &'a HashMap<ObjectId, FbxCamera>,
+    lights: &'a HashMap<ObjectId, FbxLight>,
+    material_inserts: &'a HashMap<TypeId, InsertMaterial>,
+    node_processors: &'a [Arc<dyn FbxNodeProcessor>],
+}
+
 fn spawn_scene(
-    fbx_file_scale: f32,
+    root_transform: Transform,
     roots: &[ObjectId],
-    hierarchy: &HashMap<ObjectId, FbxObject>,
-    models: &HashMap<ObjectId, FbxMesh>,
+    contents: &SceneContents,
+    #[cfg(feature = "animation")] animation_graph: Option<Handle<AnimationGraph>>,
 ) -> Scene {
     #[cfg(feature = "profile")]
     let _generate_scene_span = info_span!("generate_scene").entered();
 
     let mut scene_world = World::default();
-    scene_world
-        .spawn((
-           Visibility::default(),
-           Transform::from_scale(Vec3::ONE * FBX_TO_BEVY_SCALE_FACTOR * fbx_file_scale),
-           Name::from("FbxScene"),
-        ))
-        .with_children(|commands| {
-            for root in roots {
-                spawn_scene_rec(*root, commands, hierarchy, models);
-            }
+    let mut spawned = SpawnedEntities::default();
+    let mut scene_root = scene_world.spawn((
+        Visibility::default(),
+        root_transform,
+        Name::from("FbxScene"),
+    ));
+    #[cfg(feature = "animation")]
+    let animation = animation_graph.map(|graph| {
+        scene_root.insert((AnimationPlayer::default(), AnimationGraphHandle(graph)));
+        AnimationContext {
+            player: scene_root.id(),
+            path: Vec::new(),
+        }
+    });
+    scene_root.with_children(|commands| {
+        for root in roots {
+            spawn_scene_rec(
+                *root,
+                commands,
+                contents,
+                &mut spawned,
+                #[cfg(feature = "animation")]
+                animation.clone(),
+            );
+        }
+    });
+
+    // Joints can be anywhere in the hierarchy, so we can only
+    // add the skins once every node has an entity.
+    for (entity, skin) in spawned.skinned_meshes {
+        let joints: Option<Vec<_>> = skin
+            .joints
+            .iter()
+            .map(|joint| spawned.nodes.get(joint).copied())
+            .collect();
+        let Some(joints) = joints else {
+            warn!(
+                "Skin {:?} has joints missing from the scene, ignoring it",
+                skin.name
+            );
+            continue;
+        };
+        scene_world.entity_mut(entity).insert(SkinnedMesh {
+            inverse_bindposes: skin.inverse_bindposes.clone(),
+            joints,
         });
+    }
     Scene::new(scene_world)
 }
-fn spawn_scene_rec(
+
+/// Entities spawned by [`spawn_scene_rec`] that need further processing.
+#[derive(Default)]
+struct SpawnedEntities<'a> {
+    /// The entity of each spawned FBX node.
+    nodes: HashMap<ObjectId, Entity>,
+    /// Mesh entities that are deformed by a skin.
+    skinned_meshes: Vec<(Entity, &'a FbxSkin)>,
+}
+
+/// The path to a node from the scene root, used to set its [`AnimationTarget`].
+#[cfg(feature = "animation")]
+#[derive(Clone)]
+struct AnimationContext {
+    /// The entity with the [`AnimationPlayer`] of the scene.
+    player: Entity,
+    /// The names of the nodes from the scene root to the current node.
+    path: Vec<Name>,
+}
+
+fn spawn_scene_rec<'a>(
     current: ObjectId,
     commands: &mut WorldChildBuilder,
-    hierarchy: &HashMap<ObjectId, FbxObject>,
-    models: &HashMap<ObjectId, FbxMesh>,
-
+    contents: &SceneContents<'a>,
+    spawned: &mut SpawnedEntities<'a>,
+    #[cfg(feature = "animation")] mut animation: Option<AnimationContext>,
 ) {
-    let current_node = match hierarchy.get(&current) {
+    let current_node = match contents.hierarchy.get(&current) {
         Some(node) => node,
         None => return,
     };
     let mut entity = commands.spawn((
         Visibility::default(),
         current_node.transform,
+        FbxNodeSubclass(current_node.subclass.clone()),
     ));
     if let Some(name) = &current_node.name {
         entity.insert(Name::new(name.clone()));
     }
+    if !current_node.user_properties.0.is_empty() {
+        entity.insert(current_node.user_properties.clone());
+    }
+    // This must match the ids used by the animation clips, see `animation::target_id`.
+    #[cfg(feature = "animation")]
+    if let Some(animation) = animation.as_mut() {
+        let name = current_node.name.clone().unwrap_or_default();
+        animation.path.push(Name::new(name));
+        entity.insert(AnimationTarget {
+            id: AnimationTargetId::from_names(animation.path.iter()),
+            player: animation.player,
+        });
+    }
+    spawned.nodes.insert(current, entity.id());
+    let morph_weights = contents
+        .meshes
+        .get(&current)
+        .map(|mesh| &mesh.morph_weights)
+        .filter(|weights| !weights.is_empty());
+    if let (Some(mesh), Some(weights)) = (contents.meshes.get(&current), morph_weights) {
+        let first_mesh = mesh.bevy_mesh_handles.first().cloned();
+        // unwrap: `load_morphs` never loads more than `MAX_MORPH_WEIGHTS` targets.
+        entity.insert(MorphWeights::new(weights.clone(), first_mesh).unwrap());
+    }
     entity.with_children(|commands| {
-        if let Some(mesh) = models.get(&current) {
+        if let Some(mesh) = contents.meshes.get(&current) {
             for (mat, bevy_mesh) in mesh.materials.iter().zip(&mesh.bevy_mesh_handles) {
-                let mut entity = commands.spawn((MeshMaterial3d(mat.clone()), Mesh3d(bevy_mesh.clone())));
+                let mut entity =
+                    commands.spawn((Mesh3d(bevy_mesh.clone()), mesh.geometric_transform));
+                if let Some(insert_material) = contents.material_inserts.get(&mat.type_id()) {
+                    insert_material(&mut entity, mat.clone());
+                }
                 if let Some(name) = mesh.name.as_ref() {
                     entity.insert(Name::new(name.clone()));
                 }
+                if let Some(weights) = morph_weights {
+                    entity.insert(MeshMorphWeights::new(weights.clone()).unwrap());
+                }
+                if let Some(skin) = mesh.skin.as_ref() {
+                    spawned.skinned_meshes.push((entity.id(), skin));
+                }
+            }
+        }
+        if let Some(camera) = contents.cameras.get(&current) {
+            // Cameras are inactive, so that they don't all render on top
+            // of each other and of the cameras of the app.
+            let mut entity = commands.spawn((
+                Camera3d::default(),
+                Camera {
+                    is_active: false,
+                    ..Default::default()
+                },
+                camera.projection.clone(),
+                camera.transform,
+            ));
+            if let Some(name) = camera.name.as_ref() {
+                entity.insert(Name::new(name.clone()));
+            }
+        }
+        if let Some(light) = contents.lights.get(&current) {
+            let mut entity = commands.spawn(light.transform);
+            match &light.light {
+                FbxLightKind::Point(point) => entity.insert(*point),
+                FbxLightKind::Spot(spot) => entity.insert(*spot),
+                FbxLightKind::Directional(directional) => entity.insert(directional.clone()),
+            };
+            if let Some(name) = light.name.as_ref() {
+                entity.insert(Name::new(name.clone()));
             }
         }
         for node_id in &current_node.children {
-            spawn_scene_rec(*node_id, commands, hierarchy, models);
+            spawn_scene_rec(
+                *node_id,
+                commands,
+                contents,
+                spawned,
+                #[cfg(feature = "animation")]
+                animation.clone(),
+            );
         }
     });
+    let model = current
+        .to_object_handle(contents.doc)
+        .map(|obj| obj.get_typed());
+    if let Some(TypedObjectHandle::Model(model)) = model {
+        for processor in contents.node_processors {
+            processor.process(*model, current_node, &mut entity);
+        }
+    }
 }
 
 impl<'b, 'w> Loader<'b, 'w> {
     fn new(
         formats: CompressedImageFormats,
-        loaders: Vec<MaterialLoader>,
+        loaders: Vec<Arc<dyn MaterialLoader>>,
+        processors: Vec<Arc<dyn FbxNodeProcessor>>,
+        settings: &'b FbxLoaderSettings,
         load_context: &'b mut LoadContext<'w>,
     ) -> Self {
         Self {
             scene: FbxScene::default(),
             load_context,
             material_loaders: loaders,
+            node_processors: processors,
             suported_compressed_formats: formats,
+            settings,
+            basis: BasisChange::IDENTITY,
+            normal_mapped_materials: HashSet::default(),
+            // Meshes without materials use the default `StandardMaterial`.
+            material_inserts: HashMap::from([(
+                TypeId::of::<StandardMaterial>(),
+                insert_material::<StandardMaterial> as InsertMaterial,
+            )]),
         }
     }
 
@@ -207,18 +595,103 @@ impl<'b, 'w> Loader<'b, 'w> {
             .global_settings()
             .and_then(|g| g.fbx_scale())
             .unwrap_or(1.0);
+        let scale = self
+            .settings
+            .unit_scale
+            .unwrap_or(FBX_TO_BEVY_SCALE_FACTOR * fbx_scale as f32);
+        let basis = doc
+            .global_settings()
+            .and_then(|g| {
+                let up = g.signed_axis("UpAxis")?;
+                let front = g.signed_axis("FrontAxis")?;
+                let coord = g.signed_axis("CoordAxis")?;
+                BasisChange::new(up, front, coord)
+            })
+            .unwrap_or_else(|| {
+                warn!("Invalid or missing axis system in GlobalSettings, assuming Y up");
+                BasisChange::IDENTITY
+            });
+        let root_basis = match self.settings.axis_conversion {
+            FbxAxisConversion::None => BasisChange::IDENTITY,
+            FbxAxisConversion::RootTransform => basis,
+            FbxAxisConversion::Bake => {
+                self.basis = basis;
+                BasisChange::IDENTITY
+            }
+        };
+        let root_transform =
+            Transform::from_matrix(Mat4::from_scale(Vec3::splat(scale)) * root_basis.mat());
+
         let roots = doc.model_roots();
+        // Nodes that must be spawned even if there are no meshes below them.
+        let mut kept_nodes = doc.skin_joints();
+        for obj in doc.objects().filter(|_| self.settings.load_cameras) {
+            if let TypedObjectHandle::Model(TypedModelHandle::Camera(camera)) = obj.get_typed() {
+                // A broken camera doesn't prevent the rest of the scene from loading.
+                let fbx_camera = match self.load_camera(camera) {
+                    Ok(fbx_camera) => fbx_camera,
+                    Err(err) => {
+                        warn!(
+                            "Failed to load camera {:?}, ignoring it: {err:#}",
+                            camera.name()
+                        );
+                        continue;
+                    }
+                };
+                self.scene.cameras.insert(obj.object_id(), fbx_camera);
+                kept_nodes.insert(obj.object_id());
+            }
+        }
+        for obj in doc.objects().filter(|_| self.settings.load_lights) {
+            if let TypedObjectHandle::Model(TypedModelHandle::Light(light)) = obj.get_typed() {
+                // Like unsupported lights, a broken light is skipped.
+                let fbx_light = self.load_light(light).unwrap_or_else(|err| {
+                    warn!(
+                        "Failed to load light {:?}, ignoring it: {err:#}",
+                        light.name()
+                    );
+                    None
+                });
+                if let Some(fbx_light) = fbx_light {
+                    self.scene.lights.insert(obj.object_id(), fbx_light);
+                    kept_nodes.insert(obj.object_id());
+                }
+            }
+        }
         for root in &roots {
-            traverse_hierarchy(*root, &mut hierarchy);
+            let kept_nodes = Some(&kept_nodes).filter(|_| self.settings.prune_empty_nodes);
+            traverse_hierarchy(*root, kept_nodes, self.basis, &mut hierarchy)?;
         }
 
-        for obj in doc.objects() {
+        for obj in doc.objects().filter(|_| self.settings.load_meshes) {
             if let TypedObjectHandle::Model(TypedModelHandle::Mesh(mesh)) = obj.get_typed() {
                 meshes.insert(obj.object_id(), self.load_mesh(mesh).await?);
             }
         }
+        #[cfg(feature = "animation")]
+        for obj in doc.objects().filter(|_| self.settings.load_animations) {
+            if obj.class() == "AnimStack" {
+                self.load_animation(obj, &hierarchy)?;
+            }
+        }
+        #[cfg(feature = "animation")]
+        let animation_graph = self.load_animation_graph();
         let roots: Vec<_> = roots.into_iter().map(|obj| obj.object_id()).collect();
-        let scene = spawn_scene(fbx_scale as f32, &roots, &hierarchy, &meshes);
+        let scene = spawn_scene(
+            root_transform,
+            &roots,
+            &SceneContents {
+                doc: &doc,
+                hierarchy: &hierarchy,
+                meshes: &meshes,
+                cameras: &self.scene.cameras,
+                lights: &self.scene.lights,
+                material_inserts: &self.material_inserts,
+                node_processors: &self.node_processors,
+            },
+            #[cfg(feature = "animation")]
+            animation_graph,
+        );
 
         let load_context = &mut self.load_context;
         load_context.add_labeled_asset("Scene".to_string(), scene);
@@ -238,6 +711,10 @@ impl<'b, 'w> Loader<'b, 'w> {
         &mut self,
         mesh_obj: object::geometry::MeshHandle,
         num_materials: usize,
+        normal_mapped: bool,
+        skin: Option<&ControlPointSkin>,
+        morphs: Option<&ControlPointMorphs>,
+        geometric_transform: Option<Mat4>,
     ) -> anyhow::Result<Vec<Handle<Mesh>>> {
         let label = match mesh_obj.name() {
             Some(name) if !name.is_empty() => format!("FbxMesh@{name}/Primitive"),
@@ -257,21 +734,27 @@ impl<'b, 'w> Loader<'b, 'w> {
         let polygon_vertices = mesh_obj
             .polygon_vertices()
             .context("Failed to get polygon vertices")?;
+        let mesh_name = || mesh_obj.name().unwrap_or_default().to_owned();
         let triangle_pvi_indices = polygon_vertices
             .triangulate_each(triangulate::triangulate)
-            .context("Triangulation failed")?;
+            .map_err(|source| FbxLoadingError::Triangulation {
+                mesh: mesh_name(),
+                source,
+            })?;
 
         #[cfg(feature = "profile")]
         drop(triangulate_mesh);
 
-        // TODO this seems to duplicate vertices from neighboring triangles. We shouldn't
-        // do that and instead set the indice attribute of the Mesh properly.
+        let basis = self.basis;
+
         let get_position = |pos: Option<_>| -> Result<_, anyhow::Error> {
             let cpi = pos.ok_or_else(|| anyhow!("Failed to get control point index"))?;
             let point = polygon_vertices
                 .control_point(cpi)
                 .ok_or_else(|| anyhow!("Failed to get control point: cpi={:?}", cpi))?;
-            Ok(DVec3::from((point.x, point.y, point.z)).as_vec3().into())
+            Ok(basis
+                .vector(DVec3::from((point.x, point.y, point.z)).as_vec3())
+                .into())
         };
         let positions = triangle_pvi_indices
             .iter_control_point_indices()
@@ -281,10 +764,28 @@ impl<'b, 'w> Loader<'b, 'w> {
 
         debug!("Expand position lenght to {}", positions.len());
 
-        let layer = mesh_obj
-            .layers()
-            .next()
-            .ok_or_else(|| anyhow!("Failed to get layer"))?;
+        let joints = skin
+            .map(|skin| {
+                let get_joint = |pos: Option<ControlPointIndex>| -> Result<_, anyhow::Error> {
+                    let cpi = pos
+                        .ok_or_else(|| anyhow!("Failed to get control point index"))?
+                        .to_u32() as usize;
+                    let indices = skin.joint_indices.get(cpi);
+                    let weights = skin.joint_weights.get(cpi);
+                    indices
+                        .zip(weights)
+                        .map(|(indices, weights)| (*indices, *weights))
+                        .ok_or_else(|| anyhow!("No skin weights for control point: cpi={cpi}"))
+                };
+                triangle_pvi_indices
+                    .iter_control_point_indices()
+                    .map(get_joint)
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("Failed to reconstruct joint vertices")
+            })
+            .transpose()?;
+
+        let layer = mesh_obj.layers().next();
 
         let indices_per_material = || -> Result<_, anyhow::Error> {
             if num_materials == 0 {
@@ -292,12 +793,16 @@ impl<'b, 'w> Loader<'b, 'w> {
             };
             let mut indices_per_material = vec![Vec::new(); num_materials];
             let materials = layer
-                .layer_element_entries()
+                .iter()
+                .flat_map(|layer| layer.layer_element_entries())
                 .find_map(|entry| match entry.typed_layer_element() {
                     Ok(TypedLayerElementHandle::Material(handle)) => Some(handle),
                     _ => None,
                 })
-                .ok_or_else(|| anyhow!("Materials not found for mesh {:?}", mesh_obj))?
+                .ok_or_else(|| FbxLoadingError::MissingLayer {
+                    mesh: mesh_name(),
+                    layer: "Material",
+                })?
                 .materials()
                 .context("Failed to get materials")?;
             for tri_vi in triangle_pvi_indices.triangle_vertex_indices() {
@@ -318,68 +823,202 @@ impl<'b, 'w> Loader<'b, 'w> {
             }
             Ok(Some(indices_per_material))
         };
-        let normals = {
-            let normals = layer
-                .layer_element_entries()
-                .find_map(|entry| match entry.typed_layer_element() {
-                    Ok(TypedLayerElementHandle::Normal(handle)) => Some(handle),
+        let normal_layer =
+            mesh_obj
+                .layer_elements()
+                .into_iter()
+                .find_map(|element| match element {
+                    TypedLayerElementHandle::Normal(handle) => Some(handle),
                     _ => None,
-                })
-                .ok_or_else(|| anyhow!("Failed to get normals"))?
-                .normals()
-                .context("Failed to get normals")?;
-            let get_indices = |tri_vi| -> Result<_, anyhow::Error> {
-                let v = normals.normal(&triangle_pvi_indices, tri_vi)?;
-                Ok(DVec3::from((v.x, v.y, v.z)).as_vec3().into())
-            };
-            triangle_pvi_indices
-                .triangle_vertex_indices()
-                .map(get_indices)
-                .collect::<Result<Vec<_>, _>>()
-                .context("Failed to reconstruct normals vertices")?
+                });
+        // Bevy supports two UV channels, the second one is typically used for lightmaps.
+        let uv_attributes = [Mesh::ATTRIBUTE_UV_0, Mesh::ATTRIBUTE_UV_1];
+        let uv_layers: Vec<_> = mesh_obj
+            .layer_elements()
+            .into_iter()
+            .filter_map(|element| match element {
+                TypedLayerElementHandle::Uv(handle) => Some(handle),
+                _ => None,
+            })
+            .collect();
+        if uv_layers.len() > uv_attributes.len() {
+            warn!(
+                "Mesh {label} has {} UV sets, only the first {} are loaded",
+                uv_layers.len(),
+                uv_attributes.len(),
+            );
+        }
+        let uvs = uv_layers
+            .iter()
+            .take(uv_attributes.len())
+            .map(|uv_layer| {
+                let uv = uv_layer.uv()?;
+                let get_indices = |tri_vi| -> Result<_, anyhow::Error> {
+                    let v = uv.uv(&triangle_pvi_indices, tri_vi)?;
+                    let fbx_uv_space = DVec2::from((v.x, v.y)).as_vec2();
+                    let bevy_uv_space = fbx_uv_space * Vec2::new(1.0, -1.0) + Vec2::new(0.0, 1.0);
+                    Ok(bevy_uv_space.into())
+                };
+                triangle_pvi_indices
+                    .triangle_vertex_indices()
+                    .map(get_indices)
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("Failed to reconstruct UV vertices")
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // The first color set is bevy's vertex color, the others custom attributes.
+        let color_attributes = [
+            Mesh::ATTRIBUTE_COLOR,
+            FbxLoader::ATTRIBUTE_COLOR_1,
+            FbxLoader::ATTRIBUTE_COLOR_2,
+            FbxLoader::ATTRIBUTE_COLOR_3,
+        ];
+        let color_layers: Vec<_> = mesh_obj
+            .layer_elements()
+            .into_iter()
+            .filter_map(|element| match element {
+                TypedLayerElementHandle::Color(handle) => Some(handle),
+                _ => None,
+            })
+            .collect();
+        if color_layers.len() > color_attributes.len() {
+            warn!(
+                "Mesh {label} has {} color sets, only the first {} are loaded",
+                color_layers.len(),
+                color_attributes.len(),
+            );
+        }
+        // Tangents are only used by normal maps, which are baked against the
+        // tangents and binormals of the file when it has them.
+        let tangent_layers = match (
+            mesh_obj.raw_layer_elements("LayerElementTangent").first(),
+            mesh_obj.raw_layer_elements("LayerElementBinormal").first(),
+        ) {
+            (Some(&tangents), Some(&binormals)) if normal_mapped => Some((tangents, binormals)),
+            _ => None,
         };
-        let uv = {
-            let uv = layer
-                .layer_element_entries()
-                .find_map(|entry| match entry.typed_layer_element() {
-                    Ok(TypedLayerElementHandle::Uv(handle)) => Some(handle),
-                    _ => None,
-                })
-                .ok_or_else(|| anyhow!("Failed to get UV"))?
-                .uv()?;
-            let get_indices = |tri_vi| -> Result<_, anyhow::Error> {
-                let v = uv.uv(&triangle_pvi_indices, tri_vi)?;
-                let fbx_uv_space = DVec2::from((v.x, v.y)).as_vec2();
-                let bevy_uv_space = fbx_uv_space * Vec2::new(1.0, -1.0) + Vec2::new(0.0, 1.0);
-                Ok(bevy_uv_space.into())
+        let polygon_vertex_indices =
+            if normal_layer.is_some() && color_layers.is_empty() && tangent_layers.is_none() {
+                Vec::new()
+            } else {
+                polygon_vertex_indices(&triangle_pvi_indices)?
             };
-            triangle_pvi_indices
-                .triangle_vertex_indices()
-                .map(get_indices)
-                .collect::<Result<Vec<_>, _>>()
-                .context("Failed to reconstruct UV vertices")?
+        let normals = match normal_layer {
+            Some(normal_layer) => {
+                let normals = normal_layer.normals().context("Failed to get normals")?;
+                let get_indices = |tri_vi| -> Result<_, anyhow::Error> {
+                    let v = normals.normal(&triangle_pvi_indices, tri_vi)?;
+                    Ok(basis.vector(DVec3::from((v.x, v.y, v.z)).as_vec3()).into())
+                };
+                triangle_pvi_indices
+                    .triangle_vertex_indices()
+                    .map(get_indices)
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("Failed to reconstruct normals vertices")?
+            }
+            None => {
+                debug!("Mesh {label} has no normals, generating them");
+                let polygons = Polygons::new(polygon_vertices.raw_polygon_vertices());
+                let points: Vec<_> = polygon_vertices
+                    .raw_control_points()?
+                    .map(|point| DVec3::new(point.x, point.y, point.z))
+                    .collect();
+                let smoothing = mesh_obj
+                    .raw_layer_elements("LayerElementSmoothing")
+                    .first()
+                    .map(|&smoothing| Smoothing::new(smoothing, mesh_obj.node(), &polygons))
+                    .transpose()
+                    .context("Failed to get smoothing")?;
+                let generation = self.settings.normal_generation;
+                let normals = normals::generate(&points, &polygons, smoothing.as_ref(), generation)
+                    .context("Failed to generate normals")?;
+                polygon_vertex_indices
+                    .iter()
+                    .map(|&pvi| basis.vector(normals[pvi].as_vec3()).into())
+                    .collect()
+            }
         };
+        let tangents = tangent_layers
+            .map(|(tangents, binormals)| {
+                let tangents = RawLayerElement::new(tangents, "Tangents", "TangentsIndex", 3)?
+                    .per_triangle_vertex(&triangle_pvi_indices, &polygon_vertex_indices)?;
+                let binormals = RawLayerElement::new(binormals, "Binormals", "BinormalsIndex", 3)?
+                    .per_triangle_vertex(&triangle_pvi_indices, &polygon_vertex_indices)?;
+                let tangents = tangents
+                    .into_iter()
+                    .zip(binormals)
+                    .zip(&normals)
+                    .map(|((tangent, binormal), &normal)| {
+                        let tangent = basis.vector(DVec3::from_slice(tangent).as_vec3());
+                        let binormal = basis.vector(DVec3::from_slice(binormal).as_vec3());
+                        // bevy's bitangent is `normal × tangent * w`.
+                        let w = if Vec3::from(normal).cross(tangent).dot(binormal) < 0.0 {
+                            -1.0
+                        } else {
+                            1.0
+                        };
+                        tangent.extend(w).to_array()
+                    })
+                    .collect::<Vec<_>>();
+                anyhow::Ok(tangents)
+            })
+            .transpose()
+            .context("Failed to reconstruct tangents")?;
+
+        // Vertex colors are optional, a color set that can't be read is skipped
+        // instead of failing the whole mesh.
+        let color_space = self.settings.vertex_color_space;
+        let colors: Vec<_> = color_attributes
+            .into_iter()
+            .zip(&color_layers)
+            .enumerate()
+            .filter_map(|(i, (attribute, color_layer))| {
+                let colors = RawLayerElement::new(*color_layer.node(), "Colors", "ColorIndex", 4)
+                    .and_then(|colors| {
+                        colors.per_triangle_vertex(&triangle_pvi_indices, &polygon_vertex_indices)
+                    });
+                match colors {
+                    Ok(colors) => Some((
+                        attribute,
+                        colors
+                            .into_iter()
+                            .map(|c| color_space.to_linear([c[0], c[1], c[2], c[3]]))
+                            .collect::<Vec<_>>(),
+                    )),
+                    Err(err) => {
+                        warn!("Failed to read color set {i} of mesh {label}, ignoring it: {err}");
+                        None
+                    }
+                }
+            })
+            .collect();
 
-        if uv.len() != positions.len() || uv.len() != normals.len() {
+        let mismatched = |len: usize| len != positions.len();
+        if mismatched(normals.len())
+            || tangents
+                .as_ref()
+                .is_some_and(|tangents| mismatched(tangents.len()))
+            || uvs.iter().any(|uv| mismatched(uv.len()))
+            || colors.iter().any(|(_, c)| mismatched(c.len()))
+        {
             bail!(
-                "mismatched length of buffers: pos{} uv{} normals{}",
+                "mismatched length of buffers: pos{} uv{:?} normals{} colors{:?}",
                 positions.len(),
-                uv.len(),
+                uvs.iter().map(Vec::len).collect::<Vec<_>>(),
                 normals.len(),
+                colors.iter().map(|(_, c)| c.len()).collect::<Vec<_>>(),
             );
         }
 
-        // TODO: remove unused vertices from partial models
-        // this is complicated, as it also requires updating the indices.
-
         // A single mesh may have multiple materials applied to a different subset of
         // its vertices. In the following code, we create a unique mesh per material
-        // we found.
-        let full_mesh_indices: Vec<_> = triangle_pvi_indices
+        // we found, with only the vertices it uses.
+        let mut full_mesh_indices: Vec<_> = triangle_pvi_indices
             .triangle_vertex_indices()
             .map(|t| t.to_usize() as u32)
             .collect();
-        let all_indices = if let Some(per_materials) = indices_per_material()? {
+        let mut all_indices = if let Some(per_materials) = indices_per_material()? {
             per_materials
         } else {
             vec![full_mesh_indices.clone()]
@@ -387,38 +1026,124 @@ impl<'b, 'w> Loader<'b, 'w> {
 
         debug!("Material count for {label}: {}", all_indices.len());
 
-        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all());
+        // Morph targets are defined per control point, so welded vertices must
+        // come from the same control point.
+        let control_points = morphs
+            .map(|_| {
+                triangle_pvi_indices
+                    .iter_control_point_indices()
+                    .map(|cpi| {
+                        let cpi =
+                            cpi.ok_or_else(|| anyhow!("Failed to get control point index"))?;
+                        Ok(cpi.to_u32())
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .transpose()?;
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, self.settings.asset_usage);
         mesh.insert_attribute(
             Mesh::ATTRIBUTE_POSITION,
             VertexAttributeValues::Float32x3(positions),
         );
-        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, VertexAttributeValues::Float32x2(uv));
+        for (attribute, uv) in uv_attributes.into_iter().zip(uvs) {
+            mesh.insert_attribute(attribute, VertexAttributeValues::Float32x2(uv));
+        }
         mesh.insert_attribute(
             Mesh::ATTRIBUTE_NORMAL,
             VertexAttributeValues::Float32x3(normals),
         );
-        mesh.insert_indices(Indices::U32(full_mesh_indices));
-        mesh.generate_tangents()
-            .context("Failed to generate tangents")?;
+        if let Some(tangents) = tangents {
+            mesh.insert_attribute(
+                Mesh::ATTRIBUTE_TANGENT,
+                VertexAttributeValues::Float32x4(tangents),
+            );
+        }
+        for (attribute, colors) in colors {
+            mesh.insert_attribute(attribute, VertexAttributeValues::Float32x4(colors));
+        }
+        if let Some(joints) = joints {
+            let (indices, weights): (Vec<_>, Vec<_>) = joints.into_iter().unzip();
+            mesh.insert_attribute(
+                Mesh::ATTRIBUTE_JOINT_INDEX,
+                VertexAttributeValues::Uint16x4(indices),
+            );
+            mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, weights);
+        }
 
-        let all_handles = all_indices
-            .into_iter()
-            .enumerate()
-            .map(|(i, material_indices)| {
-                debug!("Material {i} has {} vertices", material_indices.len());
+        if let Some(matrix) = geometric_transform {
+            vertices::transform(&mut mesh, matrix);
+        }
+        // A mirrored mesh is inside-out unless we flip its triangles.
+        let is_mirror = basis.is_mirror()
+            != geometric_transform.is_some_and(|matrix| matrix.determinant() < 0.0);
 
-                let mut material_mesh = mesh.clone();
-                material_mesh.insert_indices(Indices::U32(material_indices));
+        // Triangle corners are loaded as individual vertices, merge the identical ones.
+        let corner_count = mesh.count_vertices();
+        let remap = vertices::weld(&mut mesh, control_points.as_deref());
+        let vertex_count = mesh.count_vertices();
+        debug!("Welded {corner_count} triangle corners into {vertex_count} vertices");
+        for indices in all_indices.iter_mut().chain([&mut full_mesh_indices]) {
+            indices.iter_mut().for_each(|i| *i = remap[*i as usize]);
+            if is_mirror {
+                indices
+                    .chunks_exact_mut(3)
+                    .for_each(|triangle| triangle.swap(1, 2));
+            }
+        }
 
-                let label = format!("{label}{i}");
+        mesh.insert_indices(vertices::indices(full_mesh_indices, vertex_count));
+        // Tangents are computed from the UVs, there is nothing to do without them.
+        if normal_mapped
+            && self.settings.generate_tangents
+            && !mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT)
+            && mesh.contains_attribute(Mesh::ATTRIBUTE_UV_0)
+        {
+            if let Err(err) = mesh.generate_tangents() {
+                warn!("Failed to generate tangents for mesh {label}, its normal map is ignored: {err}");
+            }
+        }
 
+        // The control point of each vertex, to look up its morph target offsets.
+        let control_points = control_points.map(|corner_control_points| {
+            let mut control_points = vec![0; vertex_count];
+            for (corner, vertex) in remap.iter().enumerate() {
+                control_points[*vertex as usize] = corner_control_points[corner];
+            }
+            control_points
+        });
+
+        let mut all_handles = Vec::with_capacity(all_indices.len());
+        for (i, material_indices) in all_indices.into_iter().enumerate() {
+            let label = format!("{label}{i}");
+            let (mut material_mesh, kept) = vertices::compact(&mesh, &material_indices);
+            debug!("Material {i} has {} vertices", kept.len());
+
+            if let (Some(morphs), Some(control_points)) = (morphs, &control_points) {
+                let linear = Mat3::from_mat4(geometric_transform.unwrap_or(Mat4::IDENTITY));
+                let normal_matrix = linear.inverse().transpose();
+                let targets = morphs.targets.iter().map(|offsets| {
+                    kept.iter().map(|vertex| {
+                        let (position, normal) = offsets[control_points[*vertex as usize] as usize];
+                        MorphAttributes::new(linear * position, normal_matrix * normal, Vec3::ZERO)
+                    })
+                });
+                let morph_targets =
+                    MorphTargetImage::new(targets, kept.len(), self.settings.asset_usage)
+                        .context("Failed to build morph targets")?;
                 let handle = self
                     .load_context
-                    .add_labeled_asset(label.to_string(), material_mesh);
-                self.scene.bevy_meshes.insert(handle.clone(), label);
-                handle
-            })
-            .collect();
+                    .add_labeled_asset(format!("{label}/MorphTargets"), morph_targets.0);
+                material_mesh.set_morph_targets(handle);
+                material_mesh.set_morph_target_names(morphs.names.clone());
+            }
+
+            let handle = self
+                .load_context
+                .add_labeled_asset(label.to_string(), material_mesh);
+            self.scene.bevy_meshes.insert(handle.clone(), label);
+            all_handles.push(handle);
+        }
         Ok(all_handles)
     }
 
@@ -436,29 +1161,77 @@ impl<'b, 'w> Loader<'b, 'w> {
         debug!("Loading FBX mesh: {label}");
 
         let bevy_obj = mesh_obj.geometry().context("Failed to get geometry")?;
+        // Names of the UV sets loaded as bevy UV channels, textures refer to them.
+        let uv_sets: Vec<_> = bevy_obj
+            .layer_elements()
+            .into_iter()
+            .filter_map(|element| match element {
+                TypedLayerElementHandle::Uv(handle) => Some(handle.name().unwrap_or_default()),
+                _ => None,
+            })
+            .take(2)
+            .collect();
 
         // async and iterators into for are necessary because of `async` `read_asset_bytes`
         // call in `load_video_clip`  that virally infect everything.
         // This can't even be ran in parallel, because we store already-encountered materials.
         let mut materials = Vec::new();
-        for mat in mesh_obj.materials() {
-            let mat = self.load_material(mat).await;
+        for mat in mesh_obj
+            .materials()
+            .filter(|_| self.settings.load_materials)
+        {
+            let mat = self.load_material(mat, &uv_sets).await;
             let mat = mat.context("Failed to load materials for mesh")?;
             materials.push(mat);
         }
+        let normal_mapped = materials
+            .iter()
+            .any(|material| self.normal_mapped_materials.contains(&material.id()));
         let material_count = materials.len();
         if material_count == 0 {
-            materials.push(Handle::default());
+            materials.push(Handle::<StandardMaterial>::default().untyped());
         }
 
+        let geometric =
+            geometric_transform(**mesh_obj).context("Failed to get geometric transform")?;
+        let (skin, control_point_skin) = match self.load_skin(bevy_obj, geometric)? {
+            Some((skin, control_point_skin)) => (Some(skin), Some(control_point_skin)),
+            None => (None, None),
+        };
+        // Bevy ignores the transform of skinned mesh entities, so skinned meshes
+        // have their geometric transform in their inverse bind poses instead.
+        let geometric = self.basis.matrix(geometric);
+        let (baked_transform, geometric_transform) = match self.settings.geometric_transform {
+            _ if skin.is_some() || geometric.abs_diff_eq(Mat4::IDENTITY, 1e-6) => {
+                (None, Transform::IDENTITY)
+            }
+            FbxGeometricTransform::Bake => (Some(geometric), Transform::IDENTITY),
+            FbxGeometricTransform::ChildEntity => (None, Transform::from_matrix(geometric)),
+        };
+        let control_point_morphs = self.load_morphs(bevy_obj)?;
         let bevy_mesh_handles = self
-            .load_bevy_mesh(bevy_obj, material_count)
+            .load_bevy_mesh(
+                bevy_obj,
+                material_count,
+                normal_mapped,
+                control_point_skin.as_ref(),
+                control_point_morphs.as_ref(),
+                baked_transform,
+            )
             .context("Failed to load geometry mesh")?;
 
         let mesh = FbxMesh {
             name: mesh_obj.name().map(Into::into),
             bevy_mesh_handles,
             materials,
+            skin,
+            morph_target_names: control_point_morphs
+                .as_ref()
+                .map_or_else(Vec::new, |morphs| morphs.names.clone()),
+            morph_weights: control_point_morphs
+                .as_ref()
+                .map_or_else(Vec::new, |morphs| morphs.weights.clone()),
+            geometric_transform,
         };
 
         let mesh_handle = self
@@ -470,9 +1243,373 @@ impl<'b, 'w> Loader<'b, 'w> {
         Ok(mesh)
     }
 
+    /// Load the skin deformer of `mesh_obj`, if any.
+    ///
+    /// This creates the [`SkinnedMeshInverseBindposes`] asset and returns the
+    /// joint influences of each control point of the mesh.
+    /// The `geometric_transform` of the mesh node is applied before the bind poses.
+    fn load_skin(
+        &mut self,
+        mesh_obj: object::geometry::MeshHandle,
+        geometric_transform: Mat4,
+    ) -> anyhow::Result<Option<(FbxSkin, ControlPointSkin)>> {
+        let Some(skin) = mesh_obj.skins().next() else {
+            return Ok(None);
+        };
+        let label = match skin.name() {
+            Some(name) if !name.is_empty() => format!("FbxSkin@{name}/InverseBindposes"),
+            _ => format!("FbxSkin{}/InverseBindposes", skin.object_id().raw()),
+        };
+        debug!("Loading skin: {label}");
+
+        let control_point_count = mesh_obj
+            .polygon_vertices()
+            .context("Failed to get polygon vertices")?
+            .raw_control_points()?
+            .count();
+        let mut influences = vec![Vec::new(); control_point_count];
+        let mut joints = Vec::new();
+        let mut inverse_bindposes = Vec::new();
+        for cluster in skin.clusters() {
+            let Some(link) = cluster.link() else {
+                warn!(
+                    "Skin cluster {:?} is not linked to any node, skipping",
+                    cluster.name()
+                );
+                continue;
+            };
+            let joint_index = joints.len() as u16;
+            let indexes = cluster.indexes()?;
+            let weights = cluster.weights()?;
+            if indexes.len() != weights.len() {
+                bail!(
+                    "mismatched length of cluster {:?} buffers: indexes{} weights{}",
+                    cluster.name(),
+                    indexes.len(),
+                    weights.len(),
+                );
+            }
+            for (&cpi, &weight) in indexes.iter().zip(weights) {
+                influences
+                    .get_mut(cpi as usize)
+                    .ok_or_else(|| anyhow!("Cluster control point out of range: cpi={cpi}"))?
+                    .push((joint_index, weight as f32));
+            }
+            // The bind pose maps the mesh at bind time into the joint's local space.
+            let bindpose = cluster.transform_link()?.inverse() * cluster.transform()?;
+            inverse_bindposes.push(self.basis.matrix(bindpose.as_mat4() * geometric_transform));
+            joints.push(link.object_id());
+        }
+        if joints.len() > MAX_JOINTS {
+            warn!(
+                "Skin {label} has {} joints, but bevy supports at most {MAX_JOINTS}",
+                joints.len()
+            );
+        }
+
+        let mut control_point_skin = ControlPointSkin {
+            joint_indices: Vec::with_capacity(control_point_count),
+            joint_weights: Vec::with_capacity(control_point_count),
+        };
+        for mut influence in influences {
+            // Only keep the strongest influences and normalize them,
+            // so that the sum of the kept weights is still 1.
+            influence.sort_by(|(_, lhs), (_, rhs)| rhs.total_cmp(lhs));
+            influence.truncate(MAX_INFLUENCES);
+            let total: f32 = influence.iter().map(|(_, weight)| weight).sum();
+            let mut indices = [0; MAX_INFLUENCES];
+            let mut weights = [0.0; MAX_INFLUENCES];
+            for (i, (joint, weight)) in influence.into_iter().enumerate() {
+                indices[i] = joint;
+                weights[i] = if total > 0.0 { weight / total } else { 0.0 };
+            }
+            control_point_skin.joint_indices.push(indices);
+            control_point_skin.joint_weights.push(weights);
+        }
+
+        let inverse_bindposes = self
+            .load_context
+            .add_labeled_asset(label, SkinnedMeshInverseBindposes::from(inverse_bindposes));
+        let skin = FbxSkin {
+            name: skin.name().map(Into::into),
+            joints,
+            inverse_bindposes,
+        };
+        Ok(Some((skin, control_point_skin)))
+    }
+
+    /// Load the camera of the `Camera` model `camera_obj`.
+    fn load_camera(&self, camera_obj: object::model::CameraHandle) -> anyhow::Result<FbxCamera> {
+        let attribute = camera_obj
+            .node_attribute()
+            .ok_or_else(|| anyhow!("Camera has no `NodeAttribute`"))?;
+        let p = attribute.properties_by_native_typename("FbxCamera");
+        // Missing properties have the default value of the FBX SDK.
+        let get = |name: &str, default: f32| f32::get_property(p, name).unwrap_or(default);
+        let near = get("NearPlane", 10.0);
+        let far = get("FarPlane", 4000.0);
+        let aspect_ratio = get("AspectWidth", 320.0) / get("AspectHeight", 200.0);
+        let aspect_ratio = if aspect_ratio.is_finite() && aspect_ratio > 0.0 {
+            aspect_ratio
+        } else {
+            1.0
+        };
+        let projection = match i32::get_property(p, "ProjectionType").unwrap_or(0) {
+            // Orthographic, `OrthoZoom` is the size of the larger side of the view.
+            1 => {
+                let size = get("OrthoZoom", 1.0);
+                Projection::Orthographic(OrthographicProjection {
+                    near,
+                    far,
+                    scaling_mode: ScalingMode::AutoMax {
+                        max_width: size,
+                        max_height: size,
+                    },
+                    ..OrthographicProjection::default_3d()
+                })
+            }
+            // Perspective, bevy's field of view is vertical.
+            _ => {
+                let to_vertical = |fov: f32| 2.0 * ((fov / 2.0).tan() / aspect_ratio).atan();
+                let fov = match i32::get_property(p, "ApertureMode").unwrap_or(2) {
+                    // Horizontal and vertical
+                    0 => get("FieldOfViewY", 25.115).to_radians(),
+                    // Horizontal
+                    1 => to_vertical(get("FieldOfView", 25.115).to_radians()),
+                    // Focal length, in millimeters while the film is in inches
+                    3 => {
+                        let film_width = get("FilmWidth", 0.816) * 25.4;
+                        to_vertical(2.0 * (film_width / (2.0 * get("FocalLength", 34.893))).atan())
+                    }
+                    // Vertical
+                    _ => get("FieldOfView", 25.115).to_radians(),
+                };
+                Projection::Perspective(PerspectiveProjection {
+                    fov,
+                    aspect_ratio,
+                    near,
+                    far,
+                })
+            }
+        };
+        // FBX cameras look towards +X with +Y up.
+        let basis = self.basis;
+        let transform =
+            Transform::default().looking_to(basis.vector(Vec3::X), basis.vector(Vec3::Y));
+        Ok(FbxCamera {
+            name: camera_obj.name().map(Into::into),
+            projection,
+            transform,
+        })
+    }
+
+    /// Load the light of the `Light` model `light_obj`.
+    ///
+    /// Returns `None` for volume lights, which bevy doesn't support.
+    fn load_light(
+        &self,
+        light_obj: object::model::LightHandle,
+    ) -> anyhow::Result<Option<FbxLight>> {
+        let attribute = light_obj
+            .node_attribute()
+            .ok_or_else(|| anyhow!("Light has no `NodeAttribute`"))?;
+        let p = attribute.properties_by_native_typename("FbxLight");
+        // Missing properties have the default value of the FBX SDK.
+        let get = |name: &str, default: f32| f32::get_property(p, name).unwrap_or(default);
+        let color = RGB::<f64>::get_property(p, "Color")
+            .map_or(Color::WHITE, |c| property_color([c.r, c.g, c.b, 1.0]));
+        let intensity = get("Intensity", 100.0) / 100.0;
+        let shadows_enabled = bool::get_property(p, "CastShadows").unwrap_or(true);
+        let units = self.settings.light_intensity;
+        let name = light_obj.name();
+
+        let point = || PointLight {
+            color,
+            intensity: intensity * units.lumens,
+            shadows_enabled,
+            ..Default::default()
+        };
+        let light = match i32::get_property(p, "LightType").unwrap_or(0) {
+            0 => FbxLightKind::Point(point()),
+            1 => FbxLightKind::Directional(DirectionalLight {
+                color,
+                illuminance: intensity * units.lux,
+                shadows_enabled,
+                ..Default::default()
+            }),
+            // FBX angles are the whole cone, bevy's are from the direction of the light.
+            2 => {
+                let outer_angle = (get("OuterAngle", 45.0).to_radians() / 2.0).min(FRAC_PI_2);
+                let inner_angle = (get("InnerAngle", 0.0).to_radians() / 2.0).min(outer_angle);
+                FbxLightKind::Spot(SpotLight {
+                    color,
+                    intensity: intensity * units.lumens,
+                    shadows_enabled,
+                    inner_angle,
+                    outer_angle,
+                    ..Default::default()
+                })
+            }
+            3 => {
+                warn!("Area light {name:?} is approximated as a point light");
+                FbxLightKind::Point(point())
+            }
+            light_type => {
+                warn!("Light {name:?} has unsupported type {light_type}, ignoring it");
+                return Ok(None);
+            }
+        };
+        // Bevy lights are physically based, their intensity decreases with
+        // the square of the distance. No decay (0) is the default of most
+        // exporters, so only the linear (1) and cubic (3) decays are reported.
+        let decay_type = i32::get_property(p, "DecayType").unwrap_or(0);
+        if !matches!(light, FbxLightKind::Directional(_)) && matches!(decay_type, 1 | 3) {
+            warn!(
+                "Light {name:?} has decay type {decay_type}, it is loaded with a quadratic decay"
+            );
+        }
+        let basis = self.basis;
+        let transform =
+            Transform::default().looking_to(-basis.vector(Vec3::Y), basis.vector(Vec3::Z));
+        Ok(Some(FbxLight {
+            name: name.map(Into::into),
+            light,
+            transform,
+        }))
+    }
+
+    /// Load the `AnimationStack` `stack_obj` as an [`AnimationClip`].
+    #[cfg(feature = "animation")]
+    fn load_animation(
+        &mut self,
+        stack_obj: object::ObjectHandle,
+        hierarchy: &HashMap<ObjectId, FbxObject>,
+    ) -> anyhow::Result<Handle<AnimationClip>> {
+        let label = match stack_obj.name() {
+            Some(name) if !name.is_empty() => format!("FbxAnimation@{name}"),
+            _ => format!("FbxAnimation{}", stack_obj.object_id().raw()),
+        };
+        debug!("Loading animation: {label}");
+        let clip = animation::load_clip(stack_obj, hierarchy, self.basis)
+            .with_context(|| format!("Failed to load animation {label}"))?;
+        let handle = self.load_context.add_labeled_asset(label.clone(), clip);
+        self.scene.animations.insert(label, handle.clone());
+        Ok(handle)
+    }
+
+    /// Add an [`AnimationGraph`] playing any of the loaded animations,
+    /// or `None` if there are no animations.
+    #[cfg(feature = "animation")]
+    fn load_animation_graph(&mut self) -> Option<Handle<AnimationGraph>> {
+        if self.scene.animations.is_empty() {
+            return None;
+        }
+        let mut labels: Vec<_> = self.scene.animations.keys().cloned().collect();
+        labels.sort();
+        let clips = labels
+            .iter()
+            .map(|label| self.scene.animations[label].clone());
+        let (graph, nodes) = AnimationGraph::from_clips(clips);
+        self.scene.animation_nodes = labels.into_iter().zip(nodes).collect();
+        let handle = self
+            .load_context
+            .add_labeled_asset("FbxAnimationGraph".to_string(), graph);
+        self.scene.animation_graph = Some(handle.clone());
+        Some(handle)
+    }
+
+    /// Load the blend shape deformers of `mesh_obj`, if any.
+    ///
+    /// Each `BlendShapeChannel` becomes a morph target named after the channel.
+    fn load_morphs(
+        &self,
+        mesh_obj: object::geometry::MeshHandle,
+    ) -> anyhow::Result<Option<ControlPointMorphs>> {
+        let channels: Vec<_> = mesh_obj
+            .blendshapes()
+            .flat_map(|blendshape| blendshape.blendshape_channels())
+            .collect();
+        if channels.is_empty() {
+            return Ok(None);
+        }
+        let control_point_count = mesh_obj
+            .polygon_vertices()
+            .context("Failed to get polygon vertices")?
+            .raw_control_points()?
+            .count();
+
+        let mut names = Vec::with_capacity(channels.len());
+        let mut weights = Vec::with_capacity(channels.len());
+        let mut targets = Vec::with_capacity(channels.len());
+        for channel in channels {
+            // In-between shapes are not supported, we only use the last (100%) one.
+            if channel.shapes().count() > 1 {
+                warn!(
+                    "Blend shape channel {:?} has in-between shapes, only the last one is used",
+                    channel.name(),
+                );
+            }
+            let Some(shape) = channel.shapes().last() else {
+                warn!(
+                    "Blend shape channel {:?} has no shapes, skipping",
+                    channel.name()
+                );
+                continue;
+            };
+            let indexes = shape.indexes()?;
+            let vertices = shape.vertices()?;
+            let normals = shape.normals()?;
+            if vertices.len() != indexes.len() * 3
+                || (!normals.is_empty() && normals.len() != vertices.len())
+            {
+                bail!(
+                    "mismatched length of shape {:?} buffers: indexes{} vertices{} normals{}",
+                    shape.name(),
+                    indexes.len(),
+                    vertices.len(),
+                    normals.len(),
+                );
+            }
+            let mut offsets = vec![(Vec3::ZERO, Vec3::ZERO); control_point_count];
+            for (i, &cpi) in indexes.iter().enumerate() {
+                let offset = offsets
+                    .get_mut(cpi as usize)
+                    .ok_or_else(|| anyhow!("Shape control point out of range: cpi={cpi}"))?;
+                offset.0 = self
+                    .basis
+                    .vector(DVec3::from_slice(&vertices[i * 3..]).as_vec3());
+                if !normals.is_empty() {
+                    offset.1 = self
+                        .basis
+                        .vector(DVec3::from_slice(&normals[i * 3..]).as_vec3());
+                }
+            }
+            let name = channel.name().or(shape.name()).unwrap_or_default();
+            names.push(name.to_owned());
+            weights.push((channel.deform_percent()? / 100.0) as f32);
+            targets.push(offsets);
+        }
+        if targets.len() > MAX_MORPH_WEIGHTS {
+            warn!(
+                "Mesh {:?} has {} blend shapes, but bevy supports at most {MAX_MORPH_WEIGHTS}",
+                mesh_obj.name(),
+                targets.len(),
+            );
+            names.truncate(MAX_MORPH_WEIGHTS);
+            weights.truncate(MAX_MORPH_WEIGHTS);
+            targets.truncate(MAX_MORPH_WEIGHTS);
+        }
+        Ok(Some(ControlPointMorphs {
+            names,
+            weights,
+            targets,
+        }))
+    }
+
     async fn load_video_clip(
         &mut self,
         video_clip_obj: object::video::ClipHandle<'_>,
+        color_space: FbxColorSpace,
     ) -> anyhow::Result<Image> {
         debug!("Loading texture image: {:?}", video_clip_obj.name());
 
@@ -483,9 +1620,8 @@ impl<'b, 'w> Loader<'b, 'w> {
 
         let file_ext = Path::new(&relative_filename)
             .extension()
-            .unwrap()
-            .to_str()
-            .unwrap()
+            .and_then(|ext| ext.to_str())
+            .with_context(|| format!("Texture image {relative_filename:?} has no file extension"))?
             .to_ascii_lowercase();
         let image: Vec<u8> = if let Some(content) = video_clip_obj.content() {
             // TODO: the clone here is absolutely unnecessary, but there
@@ -493,13 +1629,25 @@ impl<'b, 'w> Loader<'b, 'w> {
             // this if/else
             content.to_vec()
         } else {
-            let parent = self.load_context.path().parent().unwrap();
+            let parent = self
+                .load_context
+                .path()
+                .parent()
+                .context("FBX file path has no parent directory")?;
             let clean_relative_filename = relative_filename.replace('\\', "/");
             let image_path = parent.join(clean_relative_filename);
-            self.load_context.read_asset_bytes(image_path).await?
+            self.load_context
+                .read_asset_bytes(image_path.as_path())
+                .await
+                .map_err(|source| FbxLoadingError::TextureIo {
+                    path: image_path,
+                    source,
+                })?
         };
-        let is_srgb = false; // TODO
+        let is_srgb = color_space == FbxColorSpace::Srgb;
         let image = Image::from_buffer(
+            #[cfg(all(debug_assertions, feature = "dds"))]
+            relative_filename.to_string(),
             image.as_slice(),
             ImageType::Extension(&file_ext),
             self.suported_compressed_formats,
@@ -507,9 +1655,12 @@ impl<'b, 'w> Loader<'b, 'w> {
             ImageSampler::Descriptor(ImageSamplerDescriptor {
                 ..Default::default()
             }),
-            RenderAssetUsages::all()
+            self.settings.asset_usage,
         );
-        let image = image.context("Failed to read image buffer data")?;
+        let image = image.map_err(|source| FbxLoadingError::ImageDecode {
+            path: relative_filename.into(),
+            source,
+        })?;
         debug!(
             "Successfully loaded texture image: {:?}",
             video_clip_obj.name()
@@ -521,31 +1672,39 @@ impl<'b, 'w> Loader<'b, 'w> {
     async fn run_loader(
         &mut self,
         material_obj: object::material::MaterialHandle<'_>,
-        MaterialLoader {
-            static_load,
-            dynamic_load,
-            preprocess_textures,
-            with_textures,
-        }: MaterialLoader,
-    ) -> anyhow::Result<Option<StandardMaterial>> {
+        uv_sets: &[&str],
+        loader: &dyn MaterialLoader,
+    ) -> anyhow::Result<Option<LoadedMaterial>> {
         use crate::utils::fbx_extend::*;
         enum TextureSource<'a> {
             Processed(Image),
             Handle(TextureHandle<'a>),
         }
+        if !self.settings.load_textures {
+            return Ok(loader.with_textures(material_obj, HashMap::default()));
+        }
+        let color_space = |label| match loader.srgb_textures().contains(&label) {
+            true => FbxColorSpace::Srgb,
+            false => FbxColorSpace::Linear,
+        };
         let mut textures = HashMap::default();
         // code is a bit tricky so here is a rundown:
         // 1. Load all textures that are meant to be preprocessed by the
         //    MaterialLoader
-        for &label in dynamic_load {
+        for &label in loader.dynamic_load() {
             if let Some(texture) = material_obj.load_texture(label) {
-                let texture = self.get_texture(texture).await?;
+                let texture = self.get_texture(texture, color_space(label)).await?;
                 textures.insert(label, texture);
             }
         }
-        preprocess_textures(material_obj, &mut textures);
+        loader.preprocess_textures(material_obj, &mut textures);
+        for image in textures.values_mut() {
+            image.asset_usage = self.settings.asset_usage;
+        }
         // 2. Put the loaded images and the non-preprocessed texture labels into an iterator
+        let static_load = loader.static_load();
         let mut texture_handles = HashMap::with_capacity(textures.len() + static_load.len());
+        let mut uv1_textures = Vec::new();
         let texture_handles_iter = textures
             .drain()
             .map(|(label, image)| (label, TextureSource::Processed(image)))
@@ -556,11 +1715,27 @@ impl<'b, 'w> Loader<'b, 'w> {
             }));
         // 3. For each of those, create an image handle (with potential caching based on the texture name)
         for (label, texture) in texture_handles_iter {
+            // Preprocessed textures may combine several textures, they keep the first UV channel.
+            let uv_channel = match &texture {
+                TextureSource::Handle(texture_handle) => {
+                    let uv_set = texture_handle.properties().uv_set_or_default()?;
+                    uv_sets.iter().position(|&name| name == uv_set)
+                }
+                TextureSource::Processed(_) => None,
+            };
             let handle_label = match texture {
-                TextureSource::Handle(texture_handle) => match texture_handle.name() {
-                    Some(name) if !name.is_empty() => format!("FbxTexture@{name}"),
-                    _ => format!("FbxTexture{}", texture_handle.object_id().raw()),
-                },
+                TextureSource::Handle(texture_handle) => {
+                    let texture_label = match texture_handle.name() {
+                        Some(name) if !name.is_empty() => format!("FbxTexture@{name}"),
+                        _ => format!("FbxTexture{}", texture_handle.object_id().raw()),
+                    };
+                    // Slots with different color spaces may share a texture,
+                    // it is then decoded once for each.
+                    match color_space(label) {
+                        FbxColorSpace::Srgb => texture_label,
+                        FbxColorSpace::Linear => format!("{texture_label}/Linear"),
+                    }
+                }
                 TextureSource::Processed(_) => match material_obj.name() {
                     Some(name) if !name.is_empty() => format!("FbxTextureMat@{name}/{label}"),
                     _ => format!("FbxTextureMat{}/{label}", material_obj.object_id().raw()),
@@ -576,7 +1751,9 @@ impl<'b, 'w> Loader<'b, 'w> {
             } else {
                 let texture = match texture {
                     TextureSource::Processed(texture) => texture,
-                    TextureSource::Handle(texture) => self.get_texture(texture).await?,
+                    TextureSource::Handle(texture) => {
+                        self.get_texture(texture, color_space(label)).await?
+                    }
                 };
                 let handle = self
                     .load_context
@@ -584,15 +1761,26 @@ impl<'b, 'w> Loader<'b, 'w> {
                 self.scene.textures.insert(handle_label, handle.clone());
                 handle
             };
+            if uv_channel == Some(1) {
+                uv1_textures.push(handle.id());
+            }
             texture_handles.insert(label, handle);
         }
         // 4. Call with all the texture handles
-        Ok(with_textures(material_obj, texture_handles))
+        let mut material = loader.with_textures(material_obj, texture_handles);
+        if let Some(material) = material
+            .as_mut()
+            .and_then(LoadedMaterial::standard_material_mut)
+        {
+            set_uv_channels(material, &uv1_textures);
+        }
+        Ok(material)
     }
 
     async fn get_texture(
         &mut self,
         texture_obj: object::texture::TextureHandle<'_>,
+        color_space: FbxColorSpace,
     ) -> anyhow::Result<Image> {
         let properties = texture_obj.properties();
         let address_mode_u = {
@@ -617,7 +1805,8 @@ impl<'b, 'w> Loader<'b, 'w> {
             .video_clip()
             .context("No image data for texture object")?;
 
-        let image: Result<Image, anyhow::Error> = self.load_video_clip(video_clip_obj).await;
+        let image: Result<Image, anyhow::Error> =
+            self.load_video_clip(video_clip_obj, color_space).await;
         let mut image = image.context("Failed to load texture image")?;
 
         image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
@@ -628,10 +1817,15 @@ impl<'b, 'w> Loader<'b, 'w> {
         Ok(image)
     }
 
+    /// Load a material used by a mesh with the given UV sets.
+    ///
+    /// Materials are shared between meshes, the UV channels of their
+    /// textures are the ones of the first mesh that uses them.
     async fn load_material(
         &mut self,
         material_obj: object::material::MaterialHandle<'_>,
-    ) -> anyhow::Result<Handle<StandardMaterial>> {
+        uv_sets: &[&str],
+    ) -> anyhow::Result<UntypedHandle> {
         let label = match material_obj.name() {
             Some(name) if !name.is_empty() => format!("FbxMaterial@{name}"),
             _ => format!("FbxMaterial{}", material_obj.object_id().raw()),
@@ -645,16 +1839,24 @@ impl<'b, 'w> Loader<'b, 'w> {
 
         let mut material = None;
         let loaders = self.material_loaders.clone();
-        for &loader in &loaders {
-            if let Some(loader_material) = self.run_loader(material_obj, loader).await? {
+        for loader in &loaders {
+            if let Some(loader_material) = self.run_loader(material_obj, uv_sets, &**loader).await?
+            {
                 material = Some(loader_material);
                 break;
             }
         }
-        let material = material.context("None of the material loaders could load this material")?;
-        let handle = self
-            .load_context
-            .add_labeled_asset(label.to_string(), material);
+        let material = material.ok_or_else(|| FbxLoadingError::MaterialLoader {
+            material: material_obj.name().unwrap_or_default().to_owned(),
+        })?;
+        let normal_mapped = material.needs_tangents;
+        let (handle, insert_material) =
+            material.add_labeled_asset(label.to_string(), self.load_context);
+        self.material_inserts
+            .insert(handle.type_id(), insert_material);
+        if normal_mapped {
+            self.normal_mapped_materials.insert(handle.id());
+        }
         debug!("Successfully loaded material: {label}");
 
         self.scene.materials.insert(label, handle.clone());
@@ -662,42 +1864,85 @@ impl<'b, 'w> Loader<'b, 'w> {
     }
 }
 
-fn traverse_hierarchy(node: ModelHandle, hierarchy: &mut HashMap<ObjectId, FbxObject>) {
+/// Use the second UV channel for the textures of `material` in `uv1_textures`.
+fn set_uv_channels(material: &mut StandardMaterial, uv1_textures: &[AssetId<Image>]) {
+    let channels = [
+        (
+            &material.base_color_texture,
+            &mut material.base_color_channel,
+        ),
+        (&material.emissive_texture, &mut material.emissive_channel),
+        (
+            &material.metallic_roughness_texture,
+            &mut material.metallic_roughness_channel,
+        ),
+        (
+            &material.normal_map_texture,
+            &mut material.normal_map_channel,
+        ),
+        (&material.occlusion_texture, &mut material.occlusion_channel),
+    ];
+    for (texture, channel) in channels {
+        if texture
+            .as_ref()
+            .is_some_and(|t| uv1_textures.contains(&t.id()))
+        {
+            *channel = UvChannel::Uv1;
+        }
+    }
+}
+
+/// Collect the nodes below `node` into `hierarchy`.
+///
+/// With `kept_nodes`, only the meshes and `kept_nodes`, and their ancestors, are collected.
+fn traverse_hierarchy(
+    node: ModelHandle,
+    kept_nodes: Option<&HashSet<ObjectId>>,
+    basis: BasisChange,
+    hierarchy: &mut HashMap<ObjectId, FbxObject>,
+) -> anyhow::Result<()> {
     #[cfg(feature = "profile")]
     let _hierarchy_span = info_span!("traverse_fbx_hierarchy").entered();
 
-    traverse_hierarchy_rec(node, None, hierarchy);
+    traverse_hierarchy_rec(node, None, kept_nodes, basis, hierarchy)?;
     debug!("Tree has {} nodes", hierarchy.len());
     trace!("root: {:?}", node.object_node_id());
+    Ok(())
 }
 fn traverse_hierarchy_rec(
     node: ModelHandle,
     parent: Option<FbxTransform>,
+    kept_nodes: Option<&HashSet<ObjectId>>,
+    basis: BasisChange,
     hierarchy: &mut HashMap<ObjectId, FbxObject>,
-) -> bool {
+) -> anyhow::Result<bool> {
     let name = node.name().map(|s| s.to_owned());
-    let data = FbxTransform::from_node(node, parent);
+    let data = FbxTransform::from_node(node, parent)
+        .with_context(|| format!("Failed to load transform of node {name:?}"))?;
 
     let mut mesh_leaf = false;
-    node.child_models().for_each(|child| {
-        mesh_leaf |= traverse_hierarchy_rec(*child, Some(data), hierarchy);
-    });
-    if node.subclass() == "Mesh" {
-        mesh_leaf = true;
+    for child in node.child_models() {
+        mesh_leaf |= traverse_hierarchy_rec(*child, Some(data), kept_nodes, basis, hierarchy)?;
+    }
+    match kept_nodes {
+        Some(kept_nodes)
+            if node.subclass() != "Mesh" && !kept_nodes.contains(&node.object_id()) => {}
+        _ => mesh_leaf = true,
     }
-    // Only keep nodes that have Mesh children
+    // When pruning, only keep nodes that have Mesh, skin joint, camera or light children
     // (ie defines something visible in the scene)
     // I've found some very unwindy FBX files with several thousand
     // nodes that served no practical purposes,
-    // This also trims deformers and limb nodes, which we currently
-    // do not support
+    // This also trims limb nodes not used by any skin
     if mesh_leaf {
         let fbx_object = FbxObject {
             name,
-            transform: data.as_local_transform(parent.as_ref().map(|p| p.global)),
+            subclass: node.subclass().to_owned(),
+            user_properties: user_properties(*node),
+            transform: basis.transform(data.as_local_transform(parent.as_ref().map(|p| p.global))),
             children: node.child_models().map(|c| c.object_id()).collect(),
         };
         hierarchy.insert(node.object_id(), fbx_object);
     }
-    mesh_leaf
+    Ok(mesh_leaf)
 }
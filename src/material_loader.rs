@@ -1,10 +1,16 @@
-#[cfg(feature = "maya_3dsmax_pbr")]
 use crate::utils::fbx_extend::*;
 
+use std::{any::Any, sync::Arc};
+
 use bevy::{
+    asset::{LoadContext, UntypedHandle},
+    color::{Alpha, ColorToComponents, LinearRgba},
+    log::warn,
+    math::{FloatExt, UVec2, Vec2},
+    pbr::{Material, MeshMaterial3d, StandardMaterial},
     prelude::AlphaMode,
-    pbr::{StandardMaterial},
-    prelude::{Color, Handle, Image},
+    prelude::{Color, EntityWorldMut, Handle, Image},
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
     utils::HashMap,
 };
 use fbxcel_dom::v7400::{data::material::ShadingModel, object::material::MaterialHandle};
@@ -12,47 +18,228 @@ use rgb::RGB;
 
 /// Load materials from an FBX file.
 ///
-/// Define your own to extend `bevy_mod_fbx`'s material loading capabilities.
-#[derive(Clone, Copy)]
-pub struct MaterialLoader {
-    /// The FBX texture field name used by the material you are loading.
+/// Implement it to extend `bevy_mod_fbx`'s material loading capabilities,
+/// with any bevy [`Material`], such as an `ExtendedMaterial` or your own shader.
+/// Loaders are stored as trait objects in the [`FbxMaterialLoaders`] resource,
+/// so they can hold their own configuration.
+///
+/// The loaders are tried in order, the first one to return a material from
+/// [`MaterialLoader::with_textures`] is used.
+///
+/// See [`StandardMaterialLoader`] for a simpler way to load [`StandardMaterial`]s.
+/// Closures taking a [`MaterialHandle`] and returning an `Option<LoadedMaterial>`
+/// are loaders too, for materials without textures.
+///
+/// FBX materials are [`MaterialHandle`]s of the re-exported [`fbxcel_dom`].
+///
+/// [`FbxMaterialLoaders`]: crate::FbxMaterialLoaders
+/// [`fbxcel_dom`]: crate::fbxcel_dom
+pub trait MaterialLoader: Send + Sync + 'static {
+    /// The FBX texture field names used by the material you are loading.
     ///
     /// Textures declared here are directly passed to `with_textures` without modification,
     /// this enables caching and re-using textures without re-reading the files
     /// multiple times over.
-    ///
-    /// They are loaded by the [`FbxLoader`] and provided to the other functions
-    /// defined in the rest of this struct, associated with their names in a `HashMap`.
-    ///
-    /// [`FbxLoader`]: crate::FbxLoader
-    pub static_load: &'static [&'static str],
+    fn static_load(&self) -> &[&'static str] {
+        &[]
+    }
 
-    /// The FBX texture field name used by textures you wish to transform.
+    /// The FBX texture field names used by textures you wish to transform.
     ///
     /// Textures declared here are passed to `preprocess_textures` for further
     /// processing, enabling preprocessing.
+    fn dynamic_load(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// The textures of `static_load` and `dynamic_load` which are in sRGB,
+    /// such as base color and emissive textures.
     ///
-    /// They are loaded by the [`FbxLoader`] and provided to the other functions
-    /// defined in the rest of this struct, associated with their names in a `HashMap`.
-    ///
-    /// [`FbxLoader`]: crate::FbxLoader
-    pub dynamic_load: &'static [&'static str],
+    /// The other textures, such as normal, metallic, roughness or occlusion maps,
+    /// are linear.
+    fn srgb_textures(&self) -> &[&'static str] {
+        &[]
+    }
 
-    /// Run some math on the loaded textures, handy if you have to convert between texture
-    /// formats or swap color channels.
+    /// Run some math on the textures declared in `dynamic_load`, handy if you have to
+    /// convert between texture formats or swap color channels.
     ///
-    /// To update, remove or add textures, return the `HashMap` with the new values.
+    /// To update, remove or add textures, update the `HashMap` with the new values.
     ///
     /// The `Image`s are then added to the asset store (`Assets<Image>`) and a handle
     /// to them is passed to `with_textures` in additions to the handles of the textures
-    /// declared in the `static_load` field.
+    /// declared in `static_load`. Their `asset_usage` is set to the one of the
+    /// `FbxLoaderSettings`, like the other textures.
+    fn preprocess_textures(
+        &self,
+        _material: MaterialHandle,
+        _images: &mut HashMap<&'static str, Image>,
+    ) {
+    }
+
+    /// Create the material of `material`, with the textures loaded from
+    /// `static_load` and `preprocess_textures`, by name.
+    ///
+    /// Return `None` if this loader doesn't support `material`,
+    /// to let the next loaders try.
+    ///
+    /// Tangents are only generated for the meshes of [`StandardMaterial`]s with a normal map.
+    /// If your material uses a normal map, or anything else relying on tangents,
+    /// create it with [`LoadedMaterial::with_tangents`].
+    fn with_textures(
+        &self,
+        material: MaterialHandle,
+        textures: HashMap<&'static str, Handle<Image>>,
+    ) -> Option<LoadedMaterial>;
+}
+
+impl<F> MaterialLoader for F
+where
+    F: Fn(MaterialHandle) -> Option<LoadedMaterial> + Send + Sync + 'static,
+{
+    fn with_textures(
+        &self,
+        material: MaterialHandle,
+        _textures: HashMap<&'static str, Handle<Image>>,
+    ) -> Option<LoadedMaterial> {
+        self(material)
+    }
+}
+
+/// A material created by a [`MaterialLoader`], of any [`Material`] type.
+///
+/// The `FbxLoader` adds it as a labeled asset, and the mesh entities using it
+/// get the [`MeshMaterial3d`] of its type.
+/// The type of material must be registered in the app with its `MaterialPlugin`.
+///
+/// Tangents are not detected for other materials than [`StandardMaterial`],
+/// use [`LoadedMaterial::with_tangents`] if the meshes using your material need them.
+pub struct LoadedMaterial {
+    material: Box<dyn ErasedMaterial>,
+    /// Whether the meshes using the material need tangents, such as for normal maps.
+    ///
+    /// This is set by [`LoadedMaterial::new`] for [`StandardMaterial`]s with a normal map,
+    /// and is `false` for any other material.
+    pub needs_tangents: bool,
+}
+impl LoadedMaterial {
+    /// Wrap the `material` created by a [`MaterialLoader`].
+    ///
+    /// Tangents are only generated for [`StandardMaterial`]s with a normal map.
+    pub fn new<M: Material>(material: M) -> Self {
+        let needs_tangents = (&material as &dyn Any)
+            .downcast_ref::<StandardMaterial>()
+            .is_some_and(|material| material.normal_map_texture.is_some());
+        Self::with_tangents(material, needs_tangents)
+    }
+
+    /// Wrap the `material` created by a [`MaterialLoader`],
+    /// generating tangents for the meshes using it if `needs_tangents` is set.
+    pub fn with_tangents<M: Material>(material: M, needs_tangents: bool) -> Self {
+        Self {
+            material: Box::new(material),
+            needs_tangents,
+        }
+    }
+
+    /// The material, if it is a [`StandardMaterial`].
+    pub fn standard_material_mut(&mut self) -> Option<&mut StandardMaterial> {
+        self.material.as_any_mut().downcast_mut()
+    }
+
+    /// Add the material as a labeled asset of `load_context`.
+    ///
+    /// Also returns how to add the material to the mesh entities.
+    pub(crate) fn add_labeled_asset(
+        self,
+        label: String,
+        load_context: &mut LoadContext,
+    ) -> (UntypedHandle, InsertMaterial) {
+        let insert = self.material.insert_material();
+        (self.material.add_labeled_asset(label, load_context), insert)
+    }
+}
+
+/// Inserts the [`MeshMaterial3d`] of the material of the given handle.
+pub(crate) type InsertMaterial = fn(&mut EntityWorldMut, UntypedHandle);
+
+pub(crate) fn insert_material<M: Material>(entity: &mut EntityWorldMut, handle: UntypedHandle) {
+    entity.insert(MeshMaterial3d::<M>(handle.typed()));
+}
+
+/// A [`Material`] with its type erased.
+trait ErasedMaterial: Send + Sync {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn add_labeled_asset(
+        self: Box<Self>,
+        label: String,
+        load_context: &mut LoadContext,
+    ) -> UntypedHandle;
+    fn insert_material(&self) -> InsertMaterial;
+}
+impl<M: Material> ErasedMaterial for M {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn add_labeled_asset(
+        self: Box<Self>,
+        label: String,
+        load_context: &mut LoadContext,
+    ) -> UntypedHandle {
+        load_context.add_labeled_asset(label, *self).untyped()
+    }
+    fn insert_material(&self) -> InsertMaterial {
+        insert_material::<M>
+    }
+}
+
+/// A [`MaterialLoader`] of [`StandardMaterial`]s, defined by functions.
+///
+/// This is how the default loaders are defined.
+#[derive(Clone, Copy)]
+pub struct StandardMaterialLoader {
+    /// See [`MaterialLoader::static_load`].
+    pub static_load: &'static [&'static str],
+
+    /// See [`MaterialLoader::dynamic_load`].
+    pub dynamic_load: &'static [&'static str],
+
+    /// See [`MaterialLoader::srgb_textures`].
+    pub srgb_textures: &'static [&'static str],
+
+    /// See [`MaterialLoader::preprocess_textures`].
     pub preprocess_textures: fn(MaterialHandle, &mut HashMap<&'static str, Image>),
 
     /// Create and return the bevy [`StandardMaterial`] based on the [`Handle<Image>`] loaded
-    /// from the return value of `preprocess_textures`.
+    /// from `static_load` and the return value of `preprocess_textures`.
     pub with_textures:
         fn(MaterialHandle, HashMap<&'static str, Handle<Image>>) -> Option<StandardMaterial>,
 }
+impl MaterialLoader for StandardMaterialLoader {
+    fn static_load(&self) -> &[&'static str] {
+        self.static_load
+    }
+    fn dynamic_load(&self) -> &[&'static str] {
+        self.dynamic_load
+    }
+    fn srgb_textures(&self) -> &[&'static str] {
+        self.srgb_textures
+    }
+    fn preprocess_textures(
+        &self,
+        material: MaterialHandle,
+        images: &mut HashMap<&'static str, Image>,
+    ) {
+        (self.preprocess_textures)(material, images);
+    }
+    fn with_textures(
+        &self,
+        material: MaterialHandle,
+        textures: HashMap<&'static str, Handle<Image>>,
+    ) -> Option<LoadedMaterial> {
+        (self.with_textures)(material, textures).map(LoadedMaterial::new)
+    }
+}
 
 const SPECULAR_TO_METALLIC_RATIO: f32 = 0.8;
 
@@ -61,7 +248,7 @@ const SPECULAR_TO_METALLIC_RATIO: f32 = 0.8;
 ///
 /// Note that the conversion has very poor fidelity, since Phong doesn't map well
 /// to PBR.
-pub const LOAD_LAMBERT_PHONG: MaterialLoader = MaterialLoader {
+pub const LOAD_LAMBERT_PHONG: StandardMaterialLoader = StandardMaterialLoader {
     static_load: &[
         "NormalMap",
         "EmissiveColor",
@@ -69,6 +256,7 @@ pub const LOAD_LAMBERT_PHONG: MaterialLoader = MaterialLoader {
         "TransparentColor",
     ],
     dynamic_load: &[],
+    srgb_textures: &["EmissiveColor", "DiffuseColor", "TransparentColor"],
     preprocess_textures: |_, _| {},
     with_textures: |material_obj, textures| {
         use AlphaMode::{Blend, Opaque};
@@ -115,9 +303,10 @@ pub const LOAD_LAMBERT_PHONG: MaterialLoader = MaterialLoader {
 ///
 /// Picks up the non-texture material values if possible,
 /// otherwise it will just look like white clay.
-pub const LOAD_FALLBACK: MaterialLoader = MaterialLoader {
+pub const LOAD_FALLBACK: StandardMaterialLoader = StandardMaterialLoader {
     static_load: &[],
     dynamic_load: &[],
+    srgb_textures: &[],
     preprocess_textures: |_, _| {},
     with_textures: |material_obj, _| {
         let properties = material_obj.properties();
@@ -149,22 +338,312 @@ pub const LOAD_FALLBACK: MaterialLoader = MaterialLoader {
     },
 };
 
-#[cfg(feature = "maya_3dsmax_pbr")]
-mod maya_consts {
-    pub const PBR_TYPE_ID: i32 = 1166017;
-    pub const DEFAULT_ROUGHNESS: f32 = 0.089;
-    pub const DEFAULT_METALIC: f32 = 0.01;
+/// Load Autodesk Standard Surface materials, exported by Maya for its `standardSurface`
+/// and Arnold's `aiStandardSurface`.
+///
+/// Base, specular, metalness, transmission, coat and emission map to their
+/// [`StandardMaterial`] counterparts, as do the textures of the base color,
+/// metalness, specular roughness, emission color and normal.
+/// Bevy has no sheen, so the sheen layer is ignored.
+pub const LOAD_STANDARD_SURFACE: StandardMaterialLoader = StandardMaterialLoader {
+    static_load: &["Maya|baseColor", "Maya|emissionColor", "Maya|normalCamera"],
+    dynamic_load: &["Maya|metalness", "Maya|specularRoughness"],
+    srgb_textures: &["Maya|baseColor", "Maya|emissionColor"],
+    preprocess_textures: |_, images| {
+        let metallic = images.remove("Maya|metalness");
+        let roughness = images.remove("Maya|specularRoughness");
+        if let Some(image) = metallic_roughness(metallic, roughness, false) {
+            images.insert("Metallic_Roughness", image);
+        }
+    },
+    with_textures: |material, textures| {
+        // Lambert and Phong materials don't have these properties.
+        material.get_color("Maya|baseColor")?;
+        material.get_f32("Maya|specularRoughness")?;
+        // The defaults of the Standard Surface specification.
+        let factor = |name, default| material.get_f32(name).unwrap_or(default);
+        let color = |name| {
+            material
+                .get_color(name)
+                .map_or(Color::WHITE, |c| ColorAdapter(c).into())
+        };
+
+        let base_color_texture = textures.get("Maya|baseColor").cloned();
+        let base_color = match base_color_texture {
+            Some(_) => Color::WHITE,
+            None => color("Maya|baseColor"),
+        };
+        let emissive_texture = textures.get("Maya|emissionColor").cloned();
+        let emissive = match emissive_texture {
+            Some(_) => Color::WHITE,
+            None => color("Maya|emissionColor"),
+        };
+        let metallic_roughness_texture = textures.get("Metallic_Roughness").cloned();
+        // Textured factors are multiplied by the texture.
+        let is_textured =
+            |name| metallic_roughness_texture.is_some() && material.load_texture(name).is_some();
+        let metallic = match is_textured("Maya|metalness") {
+            true => 1.0,
+            false => factor("Maya|metalness", 0.0),
+        };
+        let roughness = match is_textured("Maya|specularRoughness") {
+            true => 1.0,
+            false => factor("Maya|specularRoughness", 0.2),
+        };
+        let opacity = material.get_color("Maya|opacity").map_or(1.0, |opacity| {
+            (opacity.r + opacity.g + opacity.b) as f32 / 3.0
+        });
+        let ior = factor("Maya|specularIOR", 1.5);
+        let f0 = ((ior - 1.0) / (ior + 1.0)).powi(2) * factor("Maya|specular", 1.0);
+        Some(StandardMaterial {
+            base_color: scale(base_color, factor("Maya|base", 0.8)).with_alpha(opacity),
+            base_color_texture,
+            metallic,
+            perceptual_roughness: roughness,
+            metallic_roughness_texture,
+            // Bevy's reflectance of 0.5 is the 4% reflectance of an IOR of 1.5.
+            reflectance: (f0 / 0.16).sqrt(),
+            ior,
+            specular_transmission: factor("Maya|transmission", 0.0),
+            clearcoat: factor("Maya|coat", 0.0),
+            clearcoat_perceptual_roughness: factor("Maya|coatRoughness", 0.1),
+            emissive: scale(emissive, factor("Maya|emission", 0.0)).to_linear(),
+            emissive_texture,
+            // Maya's normal maps are Y+, like bevy's.
+            normal_map_texture: textures.get("Maya|normalCamera").cloned(),
+            alpha_mode: if opacity < 1.0 {
+                AlphaMode::Blend
+            } else {
+                AlphaMode::Opaque
+            },
+            ..Default::default()
+        })
+    },
+};
+
+/// Load 3ds Max's Physical Material.
+///
+/// Base color, reflectivity, roughness (or glossiness with `roughness_inv`), metalness,
+/// transparency, coating and emission map to their [`StandardMaterial`] counterparts,
+/// as do the base color, roughness, metalness, bump and emission color maps.
+/// The bump map is expected to be a normal map.
+///
+/// The transparency map is loaded as the `specular_transmission_texture`, which requires
+/// the `pbr_transmission_textures` feature. Without it, only the transparency value is used.
+pub const LOAD_3DSMAX_PHYSICAL: StandardMaterialLoader = StandardMaterialLoader {
+    static_load: &[
+        "3dsMax|Parameters|base_color_map",
+        "3dsMax|Parameters|bump_map",
+        "3dsMax|Parameters|emit_color_map",
+        #[cfg(feature = "pbr_transmission_textures")]
+        "3dsMax|Parameters|transparency_map",
+    ],
+    dynamic_load: &[
+        "3dsMax|Parameters|metalness_map",
+        "3dsMax|Parameters|roughness_map",
+    ],
+    srgb_textures: &[
+        "3dsMax|Parameters|base_color_map",
+        "3dsMax|Parameters|emit_color_map",
+    ],
+    preprocess_textures: |material, images| {
+        let metallic = images.remove("3dsMax|Parameters|metalness_map");
+        let roughness = images.remove("3dsMax|Parameters|roughness_map");
+        let glossiness = material
+            .get_bool("3dsMax|Parameters|roughness_inv")
+            .unwrap_or(false);
+        if let Some(image) = metallic_roughness(metallic, roughness, glossiness) {
+            images.insert("Metallic_Roughness", image);
+        }
+    },
+    with_textures: |material, textures| {
+        // Only the Physical Material has this property.
+        material.get_bool("3dsMax|Parameters|roughness_inv")?;
+        // The defaults of the Physical Material in 3ds Max.
+        let factor = |name, default| material.get_f32(name).unwrap_or(default);
+        let color = |name| {
+            material
+                .get_color(name)
+                .map_or(Color::WHITE, |c| ColorAdapter(c).into())
+        };
+        let inverted = |name| material.get_bool(name).unwrap_or(false);
+
+        let base_color_texture = textures.get("3dsMax|Parameters|base_color_map").cloned();
+        let base_color = match base_color_texture {
+            Some(_) => Color::WHITE,
+            None => color("3dsMax|Parameters|base_color"),
+        };
+        let emissive_texture = textures.get("3dsMax|Parameters|emit_color_map").cloned();
+        let emissive = match emissive_texture {
+            Some(_) => Color::WHITE,
+            None => color("3dsMax|Parameters|emit_color"),
+        };
+        let metallic_roughness_texture = textures.get("Metallic_Roughness").cloned();
+        // Textured factors are multiplied by the texture.
+        let is_textured =
+            |name| metallic_roughness_texture.is_some() && material.load_texture(name).is_some();
+        let metallic = match is_textured("3dsMax|Parameters|metalness_map") {
+            true => 1.0,
+            false => factor("3dsMax|Parameters|metalness", 0.0),
+        };
+        let roughness = match is_textured("3dsMax|Parameters|roughness_map") {
+            true => 1.0,
+            false => factor("3dsMax|Parameters|roughness", 0.0),
+        };
+        let roughness = match inverted("3dsMax|Parameters|roughness_inv") {
+            true if !is_textured("3dsMax|Parameters|roughness_map") => 1.0 - roughness,
+            _ => roughness,
+        };
+        let coat_roughness = factor("3dsMax|Parameters|coat_roughness", 0.0);
+        let coat_roughness = match inverted("3dsMax|Parameters|coat_roughness_inv") {
+            true => 1.0 - coat_roughness,
+            false => coat_roughness,
+        };
+        // The transparency map is only loaded with the `pbr_transmission_textures` feature.
+        let transparency = match textures.get("3dsMax|Parameters|transparency_map") {
+            Some(_) => 1.0,
+            None => factor("3dsMax|Parameters|transparency", 0.0),
+        };
+        let ior = factor("3dsMax|Parameters|trans_ior", 1.52);
+        let f0 =
+            ((ior - 1.0) / (ior + 1.0)).powi(2) * factor("3dsMax|Parameters|reflectivity", 1.0);
+        Some(StandardMaterial {
+            base_color: scale(base_color, factor("3dsMax|Parameters|base_weight", 1.0)),
+            base_color_texture,
+            metallic,
+            perceptual_roughness: roughness,
+            metallic_roughness_texture,
+            reflectance: (f0 / 0.16).sqrt(),
+            ior,
+            specular_transmission: transparency,
+            #[cfg(feature = "pbr_transmission_textures")]
+            specular_transmission_texture: textures
+                .get("3dsMax|Parameters|transparency_map")
+                .cloned(),
+            clearcoat: factor("3dsMax|Parameters|coating", 0.0),
+            clearcoat_perceptual_roughness: coat_roughness,
+            emissive: scale(emissive, factor("3dsMax|Parameters|emission", 0.0)).to_linear(),
+            emissive_texture,
+            normal_map_texture: textures.get("3dsMax|Parameters|bump_map").cloned(),
+            // 3ds Max's normal maps are Y-.
+            flip_normal_map_y: true,
+            alpha_mode: AlphaMode::Opaque,
+            ..Default::default()
+        })
+    },
+};
+
+/// Multiply the RGB channels of `color` by `factor`, in linear space.
+fn scale(color: Color, factor: f32) -> Color {
+    LinearRgba::from_vec3(color.to_linear().to_vec3() * factor).into()
 }
 
-// Note that it's impossible to enable the `maya_pbr` feature right now.
-/// Load Maya's PBR material FBX extension.
+/// Combine greyscale `metallic` and `roughness` maps into a texture with the
+/// channels of [`StandardMaterial::metallic_roughness_texture`].
+///
+/// The maps may have any format bevy can read the texels of, their first channel is used.
+/// Maps of different sizes are resized to the largest one.
+/// A missing map is white, so that the other one still applies.
+/// With `glossiness`, the roughness map is a glossiness map, which is inverted.
+/// The sampler and asset usage are the ones of the maps.
+fn metallic_roughness(
+    metallic: Option<Image>,
+    roughness: Option<Image>,
+    glossiness: bool,
+) -> Option<Image> {
+    let source = metallic.as_ref().or(roughness.as_ref())?;
+    let (sampler, asset_usage) = (source.sampler.clone(), source.asset_usage);
+    let channel = |image: Option<Image>| match image {
+        Some(image) => Channel::new(&image).map(Some),
+        None => Some(None),
+    };
+    let (Some(metallic), Some(roughness)) = (channel(metallic), channel(roughness)) else {
+        warn!("Empty or unsupported metallic or roughness texture, ignoring them");
+        return None;
+    };
+    let size = [&metallic, &roughness]
+        .into_iter()
+        .flatten()
+        .map(|channel| channel.size)
+        .reduce(UVec2::max)?;
+    let texel =
+        |channel: &Option<Channel>, uv| channel.as_ref().map_or(1.0, |channel| channel.sample(uv));
+    let to_u8 = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let data = (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| (UVec2::new(x, y).as_vec2() + 0.5) / size.as_vec2()))
+        .flat_map(|uv| {
+            let roughness = match (&roughness, glossiness) {
+                (Some(_), true) => 1.0 - texel(&roughness, uv),
+                _ => texel(&roughness, uv),
+            };
+            [0, to_u8(roughness), to_u8(texel(&metallic, uv)), u8::MAX]
+        })
+        .collect();
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image::new(
+        size,
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+        asset_usage,
+    );
+    image.sampler = sampler;
+    Some(image)
+}
+
+/// A single channel of an image, such as a greyscale map.
+struct Channel {
+    size: UVec2,
+    values: Vec<f32>,
+}
+impl Channel {
+    /// The first channel of `image`, `None` if it is empty or bevy can't read its texels.
+    fn new(image: &Image) -> Option<Self> {
+        let size = image.size();
+        if size.x == 0 || size.y == 0 {
+            return None;
+        }
+        let values = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| (x, y)))
+            .map(|(x, y)| match image.get_color_at(x, y).ok()? {
+                // The raw value, even for sRGB textures.
+                Color::Srgba(color) => Some(color.red),
+                color => Some(color.to_linear().red),
+            })
+            .collect::<Option<_>>()?;
+        Some(Self { size, values })
+    }
+
+    /// The bilinear interpolation of the texels around `uv`.
+    fn sample(&self, uv: Vec2) -> f32 {
+        let position = (uv * self.size.as_vec2() - 0.5).max(Vec2::ZERO);
+        let max = self.size - 1;
+        let start = position.floor().as_uvec2().min(max);
+        let end = (start + 1).min(max);
+        let t = position - position.floor();
+        let value = |x: u32, y: u32| self.values[(y * self.size.x + x) as usize];
+        let top = value(start.x, start.y).lerp(value(end.x, start.y), t.x);
+        let bottom = value(start.x, end.y).lerp(value(end.x, end.y), t.x);
+        top.lerp(bottom, t.y)
+    }
+}
+
+/// The `Maya|TypeId` of Stingray PBS materials.
+const STINGRAY_PBS_TYPE_ID: i32 = 1166017;
+
+/// Load Maya's Stingray PBS materials.
+///
+/// The textures are only used when their `use_*_map` flag is set, the base values of the
+/// material are used otherwise. The metallic and roughness maps are packed into a single
+/// texture, see [`StandardMaterial::metallic_roughness_texture`].
 ///
 /// This doesn't preserve environment maps or fresnel LUT,
 /// since bevy's PBR currently doesn't support environment maps.
-///
-/// This loader is only available if the `maya_pbr` feature is enabled.
-#[cfg(feature = "maya_3dsmax_pbr")]
-pub const LOAD_MAYA_PBR: MaterialLoader = MaterialLoader {
+pub const LOAD_STINGRAY_PBS: StandardMaterialLoader = StandardMaterialLoader {
     static_load: &[
         "Maya|TEX_normal_map",
         "Maya|TEX_color_map",
@@ -172,94 +651,93 @@ pub const LOAD_MAYA_PBR: MaterialLoader = MaterialLoader {
         "Maya|TEX_emissive_map",
     ],
     dynamic_load: &["Maya|TEX_metallic_map", "Maya|TEX_roughness_map"],
-    // FIXME: this assumes both metallic map and roughness map
-    // are encoded in texture formats that can be stored as
-    // a byte array in CPU memory.
-    // This is not the case for compressed formats such as KTX or DDS
-    // FIXME: this also assumes the texture channels are 8 bit.
-    preprocess_textures: |material_handle, images| {
-        use bevy::render::render_resource::{TextureDimension::D2, TextureFormat::Rgba8UnormSrgb};
-        let mut run = || {
-            // return early if we detect this material is not Maya's PBR material
-            let mat_maya_type = material_handle.get_i32("Maya|TypeId")?;
-            if mat_maya_type != maya_consts::PBR_TYPE_ID {
-                return None;
-            }
-            let combine_colors = |colors: &[u8]| match colors {
-                // Only one channel is necessary for the metallic and roughness
-                // maps. If we assume the texture is greyscale, we can take any
-                // channel (R, G, B) and assume it's approximately the value we want.
-                &[bw, ..] => bw,
-                _ => unreachable!("A texture must at least have a single channel"),
-            };
-            // Merge the metallic and roughness map textures into one,
-            // following the GlTF standard for PBR textures.
-            // The resulting texture should have:
-            // - Green channel set to roughness
-            // - Blue channel set to metallic
-            let metallic = images.remove("Maya|TEX_metallic_map")?;
-            let rough = images.remove("Maya|TEX_roughness_map")?;
-            let image_size = metallic.texture_descriptor.size;
-            let metallic_components =
-                metallic.texture_descriptor.format.describe().components as usize;
-            let rough_components = rough.texture_descriptor.format.describe().components as usize;
-            let metallic_rough: Vec<_> = metallic
-                .data
-                .chunks(metallic_components)
-                .zip(rough.data.chunks(rough_components))
-                .flat_map(|(metallic, rough)| {
-                    [0, combine_colors(rough), combine_colors(metallic), 255]
-                })
-                .collect();
-            let metallic_rough = Image::new(image_size, D2, metallic_rough, Rgba8UnormSrgb);
-            images.insert("Metallic_Roughness", metallic_rough);
-            Some(())
-        };
-        run();
+    srgb_textures: &["Maya|TEX_color_map", "Maya|TEX_emissive_map"],
+    preprocess_textures: |material, images| {
+        let mut map = |name, flag| images.remove(name).filter(|_| uses_map(material, flag));
+        let metallic = map("Maya|TEX_metallic_map", "Maya|use_metallic_map");
+        let roughness = map("Maya|TEX_roughness_map", "Maya|use_roughness_map");
+        if let Some(image) = metallic_roughness(metallic, roughness, false) {
+            images.insert("Metallic_Roughness", image);
+        }
     },
-    with_textures: |handle, textures| {
-        // return early if we detect this material is not Maya's PBR material
-        let mat_maya_type = handle.get_i32("Maya|TypeId");
-        if mat_maya_type != Some(maya_consts::PBR_TYPE_ID) {
+    with_textures: |material, textures| {
+        if material.get_i32("Maya|TypeId") != Some(STINGRAY_PBS_TYPE_ID) {
             return None;
         }
-        let lerp = |from, to, stride| from + (to - from) * stride;
-        // Maya has fields that tells how much of the texture should be
-        // used in the final computation of the value vs the baseline value.
-        // We set the `metallic` and `perceptual_roughness` to
-        // lerp(baseline_value, fully_texture_value, use_map)
-        // so if `use_map` is 1.0, only the texture pixel counts,
-        // while if it is 0.0, only the baseline count, and anything inbetween
-        // is a mix of the two.
-        let has_rm_texture = textures.contains_key("Metallic_Roughness");
-        let use_texture = if has_rm_texture { 1.0 } else { 0.0 };
-        let use_metallic = handle
-            .get_f32("Maya|use_metallic_map")
-            .unwrap_or(use_texture);
-        let use_roughness = handle
-            .get_f32("Maya|use_roughness_map")
-            .unwrap_or(use_texture);
-        let metallic = handle
-            .get_f32("Maya|metallic")
-            .unwrap_or(maya_consts::DEFAULT_METALIC);
-        let roughness = handle
-            .get_f32("Maya|roughness")
-            .unwrap_or(maya_consts::DEFAULT_ROUGHNESS);
+        // The defaults of the Stingray PBS shader.
+        let factor = |name, default| material.get_f32(name).unwrap_or(default);
+        let color = |name| {
+            material
+                .get_color(name)
+                .map_or(Color::WHITE, |c| ColorAdapter(c).into())
+        };
+        let texture = |name, flag| {
+            textures
+                .get(name)
+                .filter(|_| uses_map(material, flag))
+                .cloned()
+        };
+
+        let base_color_texture = texture("Maya|TEX_color_map", "Maya|use_color_map");
+        let base_color = match base_color_texture {
+            Some(_) => Color::WHITE,
+            None => color("Maya|base_color"),
+        };
+        let emissive_texture = texture("Maya|TEX_emissive_map", "Maya|use_emissive_map");
+        let emissive = match emissive_texture {
+            Some(_) => Color::WHITE,
+            None => material
+                .get_color("Maya|emissive")
+                .map_or(Color::BLACK, |c| ColorAdapter(c).into()),
+        };
+        let metallic_roughness_texture = textures.get("Metallic_Roughness").cloned();
+        // Textured factors are multiplied by the texture.
+        let is_textured = |name, flag| {
+            metallic_roughness_texture.is_some()
+                && material.load_texture(name).is_some()
+                && uses_map(material, flag)
+        };
+        let metallic = match is_textured("Maya|TEX_metallic_map", "Maya|use_metallic_map") {
+            true => 1.0,
+            false => factor("Maya|metallic", 0.01),
+        };
+        let roughness = match is_textured("Maya|TEX_roughness_map", "Maya|use_roughness_map") {
+            true => 1.0,
+            false => factor("Maya|roughness", 0.089),
+        };
         Some(StandardMaterial {
             flip_normal_map_y: true,
-            base_color_texture: textures.get("Maya|TEX_color_map").cloned(),
-            normal_map_texture: textures.get("Maya|TEX_normal_map").cloned(),
-            metallic_roughness_texture: textures.get("Metallic_Roughness").cloned(),
-            metallic: lerp(metallic, 1.0, use_metallic),
-            perceptual_roughness: lerp(roughness, 1.0, use_roughness),
-            occlusion_texture: textures.get("Maya|TEX_ao_map").cloned(),
-            emissive_texture: textures.get("Maya|TEX_emissive_map").cloned(),
+            base_color,
+            base_color_texture,
+            normal_map_texture: texture("Maya|TEX_normal_map", "Maya|use_normal_map"),
+            metallic_roughness_texture,
+            metallic,
+            perceptual_roughness: roughness,
+            occlusion_texture: texture("Maya|TEX_ao_map", "Maya|use_ao_map"),
+            emissive: scale(emissive, factor("Maya|emissive_intensity", 1.0)).to_linear(),
+            emissive_texture,
             alpha_mode: AlphaMode::Opaque,
             ..Default::default()
         })
     },
 };
 
+/// Whether the `use_*_map` `flag` of a Stingray PBS material is set.
+fn uses_map(material: MaterialHandle, flag: &str) -> bool {
+    // The flags are floats, but may also be booleans.
+    let flag = material
+        .get_f32(flag)
+        .or_else(|| material.get_bool(flag).map(f32::from));
+    flag.is_some_and(|flag| flag > 0.0)
+}
+
+/// Load Maya's PBR material FBX extension.
+///
+/// This is now the [`LOAD_STINGRAY_PBS`] loader, which is always part of the
+/// [`default_loader_order`].
+#[deprecated(note = "use `LOAD_STINGRAY_PBS` instead")]
+pub const LOAD_MAYA_PBR: StandardMaterialLoader = LOAD_STINGRAY_PBS;
+
 /// The default fbx material loaders.
 ///
 /// If you don't provide your own in the [`FbxMaterialLoaders`] resource,
@@ -269,12 +747,13 @@ pub const LOAD_MAYA_PBR: MaterialLoader = MaterialLoader {
 /// and still want to fallback to the default ones.
 ///
 /// [`FbxMaterialLoaders`]: crate::FbxMaterialLoaders
-pub const fn default_loader_order() -> &'static [MaterialLoader] {
-    &[
-        #[cfg(feature = "maya_3dsmax_pbr")]
-        LOAD_MAYA_PBR,
-        LOAD_LAMBERT_PHONG,
-        LOAD_FALLBACK,
+pub fn default_loader_order() -> Vec<Arc<dyn MaterialLoader>> {
+    vec![
+        Arc::new(LOAD_STINGRAY_PBS),
+        Arc::new(LOAD_STANDARD_SURFACE),
+        Arc::new(LOAD_3DSMAX_PHYSICAL),
+        Arc::new(LOAD_LAMBERT_PHONG),
+        Arc::new(LOAD_FALLBACK),
     ]
 }
 
@@ -285,3 +764,223 @@ impl From<ColorAdapter> for Color {
         Color::srgb(rgb.r as f32, rgb.g as f32, rgb.b as f32)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bevy::{
+        asset::RenderAssetUsages,
+        image::{ImageAddressMode, ImageFilterMode, ImageSampler, ImageSamplerDescriptor},
+        pbr::StandardMaterial,
+        prelude::Image,
+        render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+        utils::HashMap,
+    };
+    use fbxcel_dom::{
+        any::AnyDocument,
+        v7400::object::{material::MaterialHandle, TypedObjectHandle},
+    };
+
+    use super::{metallic_roughness, LoadedMaterial};
+    use crate::FbxMaterialLoaders;
+
+    fn image(width: u32, height: u32, format: TextureFormat, data: Vec<u8>) -> Image {
+        let size = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        Image::new(
+            size,
+            TextureDimension::D2,
+            data,
+            format,
+            RenderAssetUsages::default(),
+        )
+    }
+
+    fn gray(width: u32, height: u32, values: &[u8]) -> Image {
+        image(width, height, TextureFormat::R8Unorm, values.to_vec())
+    }
+
+    /// The metallic and roughness channels of each texel of a packed texture.
+    fn packed(image: &Image) -> Vec<(u8, u8)> {
+        assert_eq!(image.texture_descriptor.format, TextureFormat::Rgba8Unorm);
+        image
+            .data
+            .chunks_exact(4)
+            .map(|texel| (texel[2], texel[1]))
+            .collect()
+    }
+
+    #[test]
+    fn grayscale() {
+        let metallic = gray(2, 2, &[0, 64, 128, 255]);
+        let roughness = gray(2, 2, &[10, 20, 30, 40]);
+        let image = metallic_roughness(Some(metallic), Some(roughness), false).unwrap();
+        assert_eq!(image.size().to_array(), [2, 2]);
+        assert_eq!(packed(&image), [(0, 10), (64, 20), (128, 30), (255, 40)]);
+    }
+
+    #[test]
+    fn keeps_sampler_and_asset_usage() {
+        let mut metallic = gray(1, 1, &[0]);
+        metallic.asset_usage = RenderAssetUsages::RENDER_WORLD;
+        // The filters of `nearest` are bevy's default ones, the address modes aren't.
+        metallic.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+            address_mode_u: ImageAddressMode::Repeat,
+            address_mode_v: ImageAddressMode::MirrorRepeat,
+            ..ImageSamplerDescriptor::nearest()
+        });
+        let image = metallic_roughness(Some(metallic), None, false).unwrap();
+        assert_eq!(image.asset_usage, RenderAssetUsages::RENDER_WORLD);
+        let ImageSampler::Descriptor(sampler) = image.sampler else {
+            panic!(
+                "expected the sampler of the metallic map, got {:?}",
+                image.sampler
+            );
+        };
+        let filters = [
+            sampler.mag_filter,
+            sampler.min_filter,
+            sampler.mipmap_filter,
+        ];
+        assert!(
+            filters
+                .iter()
+                .all(|filter| matches!(filter, ImageFilterMode::Nearest)),
+            "expected nearest filters, got {filters:?}"
+        );
+        assert!(matches!(sampler.address_mode_u, ImageAddressMode::Repeat));
+        assert!(matches!(
+            sampler.address_mode_v,
+            ImageAddressMode::MirrorRepeat
+        ));
+    }
+
+    #[test]
+    fn empty_map_is_ignored() {
+        let roughness = gray(1, 1, &[128]);
+        assert!(metallic_roughness(Some(gray(0, 1, &[])), Some(roughness), false).is_none());
+        assert!(metallic_roughness(None, Some(gray(2, 0, &[])), false).is_none());
+    }
+
+    #[test]
+    fn rgba_uses_first_channel() {
+        let metallic = image(
+            2,
+            1,
+            TextureFormat::Rgba8Unorm,
+            vec![50, 1, 2, 3, 100, 4, 5, 6],
+        );
+        let roughness = image(
+            2,
+            1,
+            TextureFormat::Rgba8UnormSrgb,
+            vec![150, 7, 8, 9, 200, 10, 11, 12],
+        );
+        let image = metallic_roughness(Some(metallic), Some(roughness), false).unwrap();
+        // sRGB textures keep their raw values.
+        assert_eq!(packed(&image), [(50, 150), (100, 200)]);
+    }
+
+    #[test]
+    fn sixteen_bit_and_float() {
+        let metallic = [0u16, u16::MAX, 32896, 13107]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let metallic = image(4, 1, TextureFormat::R16Unorm, metallic);
+        let roughness = [0.0f32, 1.0, 0.5, 2.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let roughness = image(4, 1, TextureFormat::R32Float, roughness);
+        let image = metallic_roughness(Some(metallic), Some(roughness), false).unwrap();
+        assert_eq!(packed(&image), [(0, 0), (255, 255), (128, 128), (51, 255)]);
+    }
+
+    #[test]
+    fn missing_map_is_white() {
+        let image = metallic_roughness(None, Some(gray(2, 1, &[10, 20])), false).unwrap();
+        assert_eq!(packed(&image), [(255, 10), (255, 20)]);
+        let image = metallic_roughness(Some(gray(2, 1, &[10, 20])), None, false).unwrap();
+        assert_eq!(packed(&image), [(10, 255), (20, 255)]);
+        assert!(metallic_roughness(None, None, false).is_none());
+    }
+
+    #[test]
+    fn glossiness_is_inverted() {
+        let image = metallic_roughness(None, Some(gray(2, 1, &[0, 55])), true).unwrap();
+        assert_eq!(packed(&image), [(255, 255), (255, 200)]);
+        // The missing roughness map stays white.
+        let image = metallic_roughness(Some(gray(1, 1, &[10])), None, true).unwrap();
+        assert_eq!(packed(&image), [(10, 255)]);
+    }
+
+    #[test]
+    fn resizes_to_largest_map() {
+        let metallic = gray(1, 1, &[200]);
+        let roughness = gray(2, 2, &[10, 20, 30, 40]);
+        let image = metallic_roughness(Some(metallic), Some(roughness), false).unwrap();
+        assert_eq!(image.size().to_array(), [2, 2]);
+        assert_eq!(packed(&image), [(200, 10), (200, 20), (200, 30), (200, 40)]);
+
+        // Wider and taller maps are both resized to the largest size in each direction.
+        let metallic = gray(2, 1, &[0, 255]);
+        let roughness = gray(1, 2, &[0, 100]);
+        let image = metallic_roughness(Some(metallic), Some(roughness), false).unwrap();
+        assert_eq!(image.size().to_array(), [2, 2]);
+        assert_eq!(packed(&image), [(0, 0), (255, 0), (0, 100), (255, 100)]);
+    }
+
+    #[test]
+    fn interpolates_when_upscaling() {
+        let metallic = gray(2, 1, &[0, 255]);
+        let roughness = gray(4, 1, &[0, 0, 0, 0]);
+        let image = metallic_roughness(Some(metallic), Some(roughness), false).unwrap();
+        let metallic: Vec<_> = packed(&image)
+            .into_iter()
+            .map(|(metallic, _)| metallic)
+            .collect();
+        assert_eq!(metallic, [0, 64, 191, 255]);
+    }
+
+    #[test]
+    fn unsupported_format() {
+        let metallic = image(1, 1, TextureFormat::Rgba16Float, vec![0; 8]);
+        assert!(metallic_roughness(Some(metallic), Some(gray(1, 1, &[0])), false).is_none());
+    }
+
+    #[test]
+    fn closures_are_loaders() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/cube.fbx");
+        let file = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+        let AnyDocument::V7400(_, doc) = AnyDocument::from_seekable_reader(file).unwrap() else {
+            panic!("unexpected FBX version");
+        };
+        let material = doc
+            .objects()
+            .find_map(|obj| match obj.get_typed() {
+                TypedObjectHandle::Material(material) => Some(material),
+                _ => None,
+            })
+            .unwrap();
+
+        let roughness = 0.25;
+        let loaders = FbxMaterialLoaders(vec![Arc::new(move |_: MaterialHandle| {
+            Some(LoadedMaterial::new(StandardMaterial {
+                perceptual_roughness: roughness,
+                ..Default::default()
+            }))
+        })]);
+        let loader = &loaders.0[0];
+        assert!(loader.static_load().is_empty() && loader.dynamic_load().is_empty());
+
+        let mut loaded = loader.with_textures(material, HashMap::new()).unwrap();
+        assert!(!loaded.needs_tangents);
+        let material = loaded.standard_material_mut().unwrap();
+        assert_eq!(material.perceptual_roughness, roughness);
+    }
+}
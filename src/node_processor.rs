@@ -0,0 +1,43 @@
+use bevy::prelude::EntityWorldMut;
+use fbxcel_dom::v7400::object::model::ModelHandle;
+
+use crate::data::FbxObject;
+
+/// Customize the entities of FBX nodes when spawning the scene of a FBX file.
+///
+/// Use it to add components the `FbxLoader` doesn't know about, such as
+/// colliders, triggers or gameplay markers, based on the node names or
+/// their [`FbxUserProperties`].
+///
+/// Processors are registered with the [`FbxNodeProcessors`] resource,
+/// and are called for each node, in the order they are registered.
+/// Closures with the same signature as [`FbxNodeProcessor::process`]
+/// are processors too.
+///
+/// The FBX node is a [`ModelHandle`] of [`fbxcel_dom`], which is re-exported
+/// so that you don't have to depend on the same version of `fbxcel-dom`.
+///
+/// Components inserted in the scene must be registered in the
+/// [`AppTypeRegistry`] of the app, like any other scene component,
+/// so that they are copied when the scene is spawned.
+///
+/// [`fbxcel_dom`]: crate::fbxcel_dom
+/// [`FbxUserProperties`]: crate::FbxUserProperties
+/// [`FbxNodeProcessors`]: crate::FbxNodeProcessors
+/// [`AppTypeRegistry`]: bevy::ecs::reflect::AppTypeRegistry
+pub trait FbxNodeProcessor: Send + Sync + 'static {
+    /// Process the entity of the FBX `node`, loaded as `object`.
+    ///
+    /// It is called once the entity and its children are spawned,
+    /// with every component added by the `FbxLoader`.
+    fn process(&self, node: ModelHandle, object: &FbxObject, entity: &mut EntityWorldMut);
+}
+
+impl<F> FbxNodeProcessor for F
+where
+    F: Fn(ModelHandle, &FbxObject, &mut EntityWorldMut) + Send + Sync + 'static,
+{
+    fn process(&self, node: ModelHandle, object: &FbxObject, entity: &mut EntityWorldMut) {
+        self(node, object, entity)
+    }
+}
@@ -1,13 +1,28 @@
 //! Collection of temporary extensions to the fbxcell_dom types
 //! until they are merged upstream.
 
-use bevy::math::{DVec2, DVec3, DVec4, EulerRot, Vec2, Vec3, Vec4};
+use std::cell::RefCell;
+
+use bevy::{
+    color::Color,
+    math::{DMat4, DVec2, DVec3, DVec4, EulerRot, Vec2, Vec3, Vec4},
+    utils::{HashMap, HashSet},
+};
 use mint::{Vector2, Vector3, Vector4};
 
 use fbxcel_dom::{
-    fbxcel::low::v7400::AttributeValue,
+    fbxcel::{
+        low::v7400::{AttributeType, AttributeValue},
+        tree::v7400::NodeHandle,
+    },
     v7400::{
+        data::mesh::{
+            layer::{MappingMode, TypedLayerElementHandle},
+            TriangleVertexIndex, TriangleVertices,
+        },
         object::{
+            deformer::{BlendShapeChannelHandle, ClusterHandle, TypedSubDeformerHandle},
+            geometry::{MeshHandle, ShapeHandle},
             material::MaterialHandle,
             model::ModelHandle,
             property::{
@@ -15,18 +30,20 @@ use fbxcel_dom::{
                 LoadProperty, ObjectProperties, PropertyHandle,
             },
             texture::TextureHandle,
-            ObjectHandle, TypedObjectHandle,
+            ObjectHandle, ObjectId, TypedObjectHandle,
         },
         Document, GlobalSettings,
     },
 };
 use rgb::{RGB, RGBA};
 
+use crate::data::{FbxPropertyValue, FbxUserProperties};
+
 pub trait MaterialHandleExt<'a> {
-    fn load_texture(&self, name: &'static str) -> Option<TextureHandle>;
+    fn load_texture(&self, name: &'static str) -> Option<TextureHandle<'_>>;
 }
 impl<'a> MaterialHandleExt<'a> for MaterialHandle<'a> {
-    fn load_texture(&self, name: &'static str) -> Option<TextureHandle> {
+    fn load_texture(&self, name: &'static str) -> Option<TextureHandle<'_>> {
         self.source_objects()
             .filter(|obj| obj.label() == Some(name))
             .filter_map(|obj| obj.object_handle())
@@ -38,26 +55,39 @@ impl<'a> MaterialHandleExt<'a> for MaterialHandle<'a> {
 }
 
 pub trait MaterialHandleQuickPropsExt<'a> {
+    fn get_color(&self, field: &str) -> Option<RGB<f64>>;
     fn get_f32(&self, field: &str) -> Option<f32>;
-    fn get_u32(&self, field: &str) -> Option<u32>;
     fn get_i32(&self, field: &str) -> Option<i32>;
+    #[allow(dead_code)]
+    fn get_u32(&self, field: &str) -> Option<u32>;
     fn get_bool(&self, field: &str) -> Option<bool>;
 }
 impl<'a> MaterialHandleQuickPropsExt<'a> for MaterialHandle<'a> {
+    fn get_color(&self, field: &str) -> Option<RGB<f64>> {
+        // Colors may have an alpha, such as the `RGBAColor`s of 3ds Max.
+        let props = self.properties();
+        let values = props.get_property(field)?.value_part();
+        let channel = |i: usize| match values.get(i)? {
+            AttributeValue::F64(value) => Some(*value),
+            AttributeValue::F32(value) => Some(f64::from(*value)),
+            _ => None,
+        };
+        Some(RGB::new(channel(0)?, channel(1)?, channel(2)?))
+    }
     fn get_f32(&self, field: &str) -> Option<f32> {
         let props = self.properties();
         let prop = props.get_property(field)?;
         prop.load_value(PrimitiveLoader::<f32>::new()).ok()
     }
-    fn get_u32(&self, field: &str) -> Option<u32> {
+    fn get_i32(&self, field: &str) -> Option<i32> {
         let props = self.properties();
         let prop = props.get_property(field)?;
-        prop.load_value(PrimitiveLoader::<u32>::new()).ok()
+        prop.load_value(PrimitiveLoader::<i32>::new()).ok()
     }
-    fn get_i32(&self, field: &str) -> Option<i32> {
+    fn get_u32(&self, field: &str) -> Option<u32> {
         let props = self.properties();
         let prop = props.get_property(field)?;
-        prop.load_value(PrimitiveLoader::<i32>::new()).ok()
+        prop.load_value(PrimitiveLoader::<u32>::new()).ok()
     }
     fn get_bool(&self, field: &str) -> Option<bool> {
         let props = self.properties();
@@ -68,16 +98,32 @@ impl<'a> MaterialHandleQuickPropsExt<'a> for MaterialHandle<'a> {
 
 pub trait GlobalSettingsExt<'a> {
     fn fbx_scale(&self) -> Option<f64>;
+    /// The axis stored in the `name` and `{name}Sign` properties
+    /// (for example `UpAxis` and `UpAxisSign`), as an axis index
+    /// (0 for X, 1 for Y and 2 for Z) and a sign.
+    fn signed_axis(&self, name: &str) -> Option<(usize, f32)>;
 }
 impl<'a> GlobalSettingsExt<'a> for GlobalSettings<'a> {
     fn fbx_scale(&self) -> Option<f64> {
         let prop = self.raw_properties().get_property("UnitScaleFactor")?;
-        let attribute = prop.value_part().get(0)?;
+        let attribute = prop.value_part().first()?;
         match attribute {
             AttributeValue::F64(scale) => Some(*scale),
             _ => None,
         }
     }
+    fn signed_axis(&self, name: &str) -> Option<(usize, f32)> {
+        let get_i32 = |name: &str| {
+            let prop = self.raw_properties().get_property(name)?;
+            match prop.value_part().first()? {
+                AttributeValue::I32(value) => Some(*value),
+                _ => None,
+            }
+        };
+        let axis = get_i32(name).filter(|axis| (0..3).contains(axis))?;
+        let sign = get_i32(&format!("{name}Sign")).unwrap_or(1);
+        Some((axis as usize, if sign < 0 { -1.0 } else { 1.0 }))
+    }
 }
 
 pub trait Loadable: Sized {
@@ -364,3 +410,438 @@ impl ModelTreeRootExt for Document {
             .collect()
     }
 }
+
+/// Read the first attribute of the `name` child node of `node` as an array.
+///
+/// `get` is one of the `AttributeValue::get_arr_*_or_type` methods,
+/// `ty` is the name of the expected type, for error messages.
+fn child_array<'a, T>(
+    node: NodeHandle<'a>,
+    name: &str,
+    ty: &str,
+    get: fn(&'a AttributeValue) -> Result<&'a [T], AttributeType>,
+) -> anyhow::Result<Option<&'a [T]>> {
+    let child = match node.first_child_by_name(name) {
+        Some(child) => child,
+        None => return Ok(None),
+    };
+    let attribute = child
+        .attributes()
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("`{name}` node has no attributes"))?;
+    let array = get(attribute).map_err(|got| {
+        anyhow::anyhow!("`{name}` has wrong type attribute: expected `[{ty}]` got {got:?}")
+    })?;
+    Ok(Some(array))
+}
+
+/// Read the first attribute of the `name` child node of `node` as a string.
+pub(crate) fn child_string<'a>(
+    node: NodeHandle<'a>,
+    name: &str,
+) -> anyhow::Result<Option<&'a str>> {
+    let child = match node.first_child_by_name(name) {
+        Some(child) => child,
+        None => return Ok(None),
+    };
+    let attribute = child
+        .attributes()
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("`{name}` node has no attributes"))?;
+    let string = attribute.get_string_or_type().map_err(|got| {
+        anyhow::anyhow!("`{name}` has wrong type attribute: expected string got {got:?}")
+    })?;
+    Ok(Some(string))
+}
+
+/// Read the first attribute of the `name` child node of `node` as a `f64` array.
+pub(crate) fn child_f64_array<'a>(
+    node: NodeHandle<'a>,
+    name: &str,
+) -> anyhow::Result<Option<&'a [f64]>> {
+    child_array(node, name, "f64", AttributeValue::get_arr_f64_or_type)
+}
+
+/// Read the first attribute of the `name` child node of `node` as a `f32` array.
+#[cfg(feature = "animation")]
+pub(crate) fn child_f32_array<'a>(
+    node: NodeHandle<'a>,
+    name: &str,
+) -> anyhow::Result<Option<&'a [f32]>> {
+    child_array(node, name, "f32", AttributeValue::get_arr_f32_or_type)
+}
+
+/// Read the first attribute of the `name` child node of `node` as a `i32` array.
+pub(crate) fn child_i32_array<'a>(
+    node: NodeHandle<'a>,
+    name: &str,
+) -> anyhow::Result<Option<&'a [i32]>> {
+    child_array(node, name, "i32", AttributeValue::get_arr_i32_or_type)
+}
+
+/// Read the first attribute of the `name` child node of `node` as a `i64` array.
+#[cfg(feature = "animation")]
+pub(crate) fn child_i64_array<'a>(
+    node: NodeHandle<'a>,
+    name: &str,
+) -> anyhow::Result<Option<&'a [i64]>> {
+    child_array(node, name, "i64", AttributeValue::get_arr_i64_or_type)
+}
+
+/// Read a 4x4 matrix stored as 16 `f64` in the `name` child node of `node`.
+fn child_matrix(node: NodeHandle, name: &str) -> anyhow::Result<Option<DMat4>> {
+    match child_f64_array(node, name)? {
+        Some(array) => {
+            let array: &[f64; 16] = array.try_into().map_err(|_| {
+                anyhow::anyhow!(
+                    "`{name}` should be a 4x4 matrix, got {} elements",
+                    array.len()
+                )
+            })?;
+            Ok(Some(DMat4::from_cols_array(array)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Access to the skinning data of a `Cluster` sub-deformer.
+///
+/// A cluster binds a set of control points of a geometry mesh to a single
+/// joint (the "link" model, usually a `LimbNode`).
+pub trait ClusterHandleExt<'a> {
+    /// The model this cluster deforms the mesh with.
+    fn link(&self) -> Option<ModelHandle<'a>>;
+    /// The control point indices influenced by this cluster.
+    ///
+    /// Empty if the cluster doesn't influence any control point.
+    fn indexes(&self) -> anyhow::Result<&'a [i32]>;
+    /// The weights associated with each element of [`Self::indexes`].
+    fn weights(&self) -> anyhow::Result<&'a [f64]>;
+    /// Global transform of the mesh at bind time.
+    fn transform(&self) -> anyhow::Result<DMat4>;
+    /// Global transform of the link model at bind time.
+    fn transform_link(&self) -> anyhow::Result<DMat4>;
+}
+impl<'a> ClusterHandleExt<'a> for ClusterHandle<'a> {
+    fn link(&self) -> Option<ModelHandle<'a>> {
+        self.source_objects()
+            .filter(|obj| obj.label().is_none())
+            .filter_map(|obj| obj.object_handle())
+            .find_map(|obj| match obj.get_typed() {
+                TypedObjectHandle::Model(o) => Some(*o),
+                _ => None,
+            })
+    }
+    fn indexes(&self) -> anyhow::Result<&'a [i32]> {
+        Ok(child_i32_array(self.node(), "Indexes")?.unwrap_or_default())
+    }
+    fn weights(&self) -> anyhow::Result<&'a [f64]> {
+        Ok(child_f64_array(self.node(), "Weights")?.unwrap_or_default())
+    }
+    fn transform(&self) -> anyhow::Result<DMat4> {
+        Ok(child_matrix(self.node(), "Transform")?.unwrap_or(DMat4::IDENTITY))
+    }
+    fn transform_link(&self) -> anyhow::Result<DMat4> {
+        child_matrix(self.node(), "TransformLink")?
+            .ok_or_else(|| anyhow::anyhow!("Cluster {:?} has no `TransformLink`", self.name()))
+    }
+}
+
+/// Access to the `NodeAttribute` of a model, which holds the properties
+/// of cameras and lights.
+pub trait NodeAttributeExt<'a> {
+    fn node_attribute(&self) -> Option<ObjectHandle<'a>>;
+}
+impl<'a> NodeAttributeExt<'a> for ModelHandle<'a> {
+    fn node_attribute(&self) -> Option<ObjectHandle<'a>> {
+        self.source_objects()
+            .filter(|obj| obj.label().is_none())
+            .filter_map(|obj| obj.object_handle())
+            .find(|obj| obj.class() == "NodeAttribute")
+    }
+}
+
+/// Read the user-defined properties of `object`, the entries of its
+/// `Properties70` with the `U` flag, except the hidden ones (`H` flag)
+/// that DCC tools add for themselves, like the `MaxHandle` of 3ds Max.
+///
+/// Properties with values that aren't a boolean, number, string, vector or color
+/// are ignored.
+pub(crate) fn user_properties(object: ObjectHandle) -> FbxUserProperties {
+    let properties = object
+        .node()
+        .first_child_by_name("Properties70")
+        .into_iter()
+        .flat_map(|properties| properties.children_by_name("P"))
+        .filter_map(|property| {
+            // The attributes of a property are its name, type, label, flags and values.
+            let attributes = property.attributes();
+            let string = |i: usize| attributes.get(i)?.get_string();
+            let (name, data_type, flags) = (string(0)?, string(1)?, string(3)?);
+            if !flags.contains('U') || flags.contains('H') {
+                return None;
+            }
+            Some((
+                name.to_owned(),
+                property_value(data_type, &attributes[4..])?,
+            ))
+        });
+    FbxUserProperties(properties.collect())
+}
+
+/// The value of a color property, such as the color of a light or a user-defined color.
+///
+/// DCC tools store color properties as the linear values they render with,
+/// the same values bevy expects, so they are not decoded from sRGB.
+pub(crate) fn property_color([r, g, b, a]: [f64; 4]) -> Color {
+    Color::linear_rgba(r as f32, g as f32, b as f32, a as f32)
+}
+
+fn property_value(data_type: &str, values: &[AttributeValue]) -> Option<FbxPropertyValue> {
+    let number = |value: &AttributeValue| match *value {
+        AttributeValue::I16(v) => Some(f64::from(v)),
+        AttributeValue::I32(v) => Some(f64::from(v)),
+        AttributeValue::I64(v) => Some(v as f64),
+        AttributeValue::F32(v) => Some(f64::from(v)),
+        AttributeValue::F64(v) => Some(v),
+        _ => None,
+    };
+    let numbers: Option<Vec<_>> = values.iter().map(number).collect();
+    let value = match (data_type, values, numbers.as_deref()) {
+        ("bool" | "Bool", _, Some(&[value])) => FbxPropertyValue::Bool(value != 0.0),
+        ("Color" | "ColorRGB", _, Some(&[r, g, b])) => {
+            FbxPropertyValue::Color(property_color([r, g, b, 1.0]))
+        }
+        ("ColorAndAlpha", _, Some(&[r, g, b, a])) => {
+            FbxPropertyValue::Color(property_color([r, g, b, a]))
+        }
+        (_, [AttributeValue::Bool(value)], _) => FbxPropertyValue::Bool(*value),
+        (_, [AttributeValue::I16(value)], _) => FbxPropertyValue::Int((*value).into()),
+        (_, [AttributeValue::I32(value)], _) => FbxPropertyValue::Int((*value).into()),
+        (_, [AttributeValue::I64(value)], _) => FbxPropertyValue::Int(*value),
+        (_, [AttributeValue::String(value)], _) => FbxPropertyValue::String(value.clone()),
+        (_, _, Some(&[value])) => FbxPropertyValue::Float(value),
+        (_, _, Some(&[x, y, z])) => FbxPropertyValue::Vector(DVec3::new(x, y, z)),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Skinning-related queries on the whole document.
+pub trait SkinJointsExt {
+    /// The object ids of all models used as joint by a skin cluster.
+    fn skin_joints(&self) -> HashSet<ObjectId>;
+}
+impl SkinJointsExt for Document {
+    fn skin_joints(&self) -> HashSet<ObjectId> {
+        self.objects()
+            .filter_map(|obj| match obj.get_typed() {
+                TypedObjectHandle::SubDeformer(TypedSubDeformerHandle::Cluster(cluster)) => {
+                    cluster.link()
+                }
+                _ => None,
+            })
+            .map(|model| model.object_id())
+            .collect()
+    }
+}
+
+/// Access to the data of a `BlendShapeChannel` sub-deformer.
+pub trait BlendShapeChannelHandleExt<'a> {
+    /// The default influence of this channel, in percent (from 0 to 100).
+    fn deform_percent(&self) -> anyhow::Result<f64>;
+}
+impl<'a> BlendShapeChannelHandleExt<'a> for BlendShapeChannelHandle<'a> {
+    fn deform_percent(&self) -> anyhow::Result<f64> {
+        let properties = self.properties_by_native_typename("FbxBlendShapeChannel");
+        match properties.get_property("DeformPercent") {
+            Some(_) => f64::get_property(properties, "DeformPercent"),
+            None => Ok(0.0),
+        }
+    }
+}
+
+/// Access to the data of a blend shape target `Shape` geometry.
+///
+/// A shape stores the offsets of a sparse set of control points
+/// of the geometry mesh deformed by the blend shape.
+pub trait ShapeHandleExt<'a> {
+    /// The control point indices moved by this shape.
+    fn indexes(&self) -> anyhow::Result<&'a [i32]>;
+    /// Position offsets of the control points in [`Self::indexes`], as `xyz` triplets.
+    fn vertices(&self) -> anyhow::Result<&'a [f64]>;
+    /// Normal offsets of the control points in [`Self::indexes`], as `xyz` triplets.
+    ///
+    /// Empty if the shape doesn't define normals.
+    fn normals(&self) -> anyhow::Result<&'a [f64]>;
+}
+impl<'a> ShapeHandleExt<'a> for ShapeHandle<'a> {
+    fn indexes(&self) -> anyhow::Result<&'a [i32]> {
+        Ok(child_i32_array(self.node(), "Indexes")?.unwrap_or_default())
+    }
+    fn vertices(&self) -> anyhow::Result<&'a [f64]> {
+        Ok(child_f64_array(self.node(), "Vertices")?.unwrap_or_default())
+    }
+    fn normals(&self) -> anyhow::Result<&'a [f64]> {
+        Ok(child_f64_array(self.node(), "Normals")?.unwrap_or_default())
+    }
+}
+
+/// Access to the layer elements of a geometry mesh.
+pub trait MeshHandleExt<'a> {
+    /// The layer elements of all the layers of the mesh, in layer order.
+    ///
+    /// The first UV layer element is the mesh's first UV channel,
+    /// the second one its second UV channel, and so on.
+    fn layer_elements(&self) -> Vec<TypedLayerElementHandle<'a>>;
+    /// The `type_name` layer element nodes of all the layers of the mesh,
+    /// in layer order, for the layer element types fbxcel-dom doesn't handle,
+    /// such as `LayerElementTangent`.
+    fn raw_layer_elements(&self, type_name: &str) -> Vec<NodeHandle<'a>>;
+}
+impl<'a> MeshHandleExt<'a> for MeshHandle<'a> {
+    fn layer_elements(&self) -> Vec<TypedLayerElementHandle<'a>> {
+        // Several layers may refer to the same layer element.
+        let mut seen = HashSet::new();
+        self.layers()
+            .flat_map(|layer| layer.layer_element_entries())
+            .filter(|entry| seen.insert((entry.type_().ok(), entry.typed_index().ok())))
+            .filter_map(|entry| entry.typed_layer_element().ok())
+            .collect()
+    }
+    fn raw_layer_elements(&self, type_name: &str) -> Vec<NodeHandle<'a>> {
+        let typed_index =
+            |node: NodeHandle<'a>| node.attributes().first().and_then(AttributeValue::get_i32);
+        let mut seen = HashSet::new();
+        self.layers()
+            .flat_map(|layer| layer.layer_element_entries())
+            .filter(|entry| entry.type_str().ok() == Some(type_name))
+            .filter_map(|entry| typed_index(entry.first_child_by_name("TypedIndex")?))
+            .filter(|&index| seen.insert(index))
+            .filter_map(|index| {
+                self.node()
+                    .children_by_name(type_name)
+                    .find(|&node| typed_index(node) == Some(index))
+            })
+            .collect()
+    }
+}
+
+/// The index in the `PolygonVertexIndex` array of each triangle vertex,
+/// which is what `ByPolygonVertex` layer elements are indexed with.
+pub fn polygon_vertex_indices(tris: &TriangleVertices) -> anyhow::Result<Vec<usize>> {
+    // `PolygonVertexIndex` doesn't expose its value, but `triangulate_each`
+    // goes through all the polygon vertices in order.
+    let order = RefCell::new(HashMap::new());
+    tris.polygon_vertices()
+        .triangulate_each(|_, poly_pvis, _| {
+            let mut order = order.borrow_mut();
+            for &pvi in poly_pvis {
+                let index = order.len();
+                order.insert(pvi, index);
+            }
+            Ok(())
+        })?;
+    let order = order.into_inner();
+    tris.triangle_vertex_indices()
+        .map(|tri_vi| {
+            tris.polygon_vertex_index(tri_vi)
+                .and_then(|pvi| order.get(&pvi).copied())
+                .ok_or_else(|| anyhow::anyhow!("No polygon vertex for triangle vertex {tri_vi:?}"))
+        })
+        .collect()
+}
+
+/// A `LayerElement*` node of a geometry mesh, read directly from its child nodes.
+///
+/// fbxcel-dom has no handle for some layer elements, and its color handle
+/// looks for a `ColorsIndex` node where files have a `ColorIndex` one.
+#[derive(Clone, Copy)]
+pub struct RawLayerElement<'a> {
+    values: &'a [f64],
+    /// Number of components of each value.
+    width: usize,
+    /// The index of the value of each mapped item, when `IndexToDirect`.
+    indices: Option<&'a [i32]>,
+    mapping_mode: MappingMode,
+}
+impl<'a> RawLayerElement<'a> {
+    /// Read the `values` array of `node`, of `width` components per value,
+    /// and its `index` array if it is referenced `IndexToDirect`.
+    pub fn new(
+        node: NodeHandle<'a>,
+        values: &str,
+        index: &str,
+        width: usize,
+    ) -> anyhow::Result<Self> {
+        let child_string = |name: &str| {
+            child_string(node, name)?
+                .ok_or_else(|| anyhow::anyhow!("`{}` node has no `{name}`", node.name()))
+        };
+        let mapping_mode = child_string("MappingInformationType")?.parse()?;
+        let indices = match child_string("ReferenceInformationType")? {
+            "Direct" => None,
+            // `Index` is the name of `IndexToDirect` in older files.
+            "IndexToDirect" | "Index" => Some(
+                child_i32_array(node, index)?
+                    .ok_or_else(|| anyhow::anyhow!("`{}` node has no `{index}`", node.name()))?,
+            ),
+            mode => anyhow::bail!("Unknown reference mode {mode:?}"),
+        };
+        let values = child_f64_array(node, values)?
+            .ok_or_else(|| anyhow::anyhow!("`{}` node has no `{values}`", node.name()))?;
+        Ok(Self {
+            values,
+            width,
+            indices,
+            mapping_mode,
+        })
+    }
+
+    /// The value of each triangle vertex of `tris`.
+    ///
+    /// `polygon_vertex_indices` are the [`polygon_vertex_indices`] of `tris`.
+    pub fn per_triangle_vertex(
+        &self,
+        tris: &TriangleVertices<'a>,
+        polygon_vertex_indices: &[usize],
+    ) -> anyhow::Result<Vec<&'a [f64]>> {
+        tris.triangle_vertex_indices()
+            .map(|tri_vi| self.get(tris, polygon_vertex_indices, tri_vi))
+            .collect()
+    }
+
+    fn get(
+        &self,
+        tris: &TriangleVertices<'a>,
+        polygon_vertex_indices: &[usize],
+        tri_vi: TriangleVertexIndex,
+    ) -> anyhow::Result<&'a [f64]> {
+        let item = match self.mapping_mode {
+            MappingMode::ByControlPoint => tris
+                .control_point_index(tri_vi)
+                .map(|cpi| cpi.to_u32() as usize),
+            MappingMode::ByPolygonVertex => polygon_vertex_indices.get(tri_vi.to_usize()).copied(),
+            MappingMode::ByPolygon => tris
+                .polygon_index(tri_vi.triangle_index())
+                .map(|poly_i| poly_i.to_usize()),
+            MappingMode::AllSame => Some(0),
+            mode @ (MappingMode::None | MappingMode::ByEdge) => {
+                anyhow::bail!("Unsupported mapping mode {mode:?}")
+            }
+        };
+        let item = item.ok_or_else(|| anyhow::anyhow!("Invalid triangle vertex {tri_vi:?}"))?;
+        let index = match self.indices {
+            Some(indices) => {
+                let index = *indices
+                    .get(item)
+                    .ok_or_else(|| anyhow::anyhow!("Index out of range: {item}"))?;
+                usize::try_from(index).map_err(|_| anyhow::anyhow!("Negative index {index}"))?
+            }
+            None => item,
+        };
+        self.values
+            .get(index * self.width..(index + 1) * self.width)
+            .ok_or_else(|| anyhow::anyhow!("Value index out of range: {index}"))
+    }
+}
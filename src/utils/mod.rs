@@ -1,2 +1,4 @@
 pub(crate) mod fbx_extend;
+pub(crate) mod normals;
 pub(crate) mod triangulate;
+pub(crate) mod vertices;
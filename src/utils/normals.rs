@@ -0,0 +1,354 @@
+//! Normal generation for the meshes that don't have normals in the file.
+
+use std::ops::Range;
+
+use anyhow::{anyhow, bail};
+use bevy::{
+    math::DVec3,
+    utils::{HashMap, HashSet},
+};
+use fbxcel_dom::fbxcel::tree::v7400::NodeHandle;
+
+use crate::{
+    loader::FbxNormalGeneration,
+    utils::fbx_extend::{child_i32_array, child_string},
+};
+
+/// The polygons of a mesh, as stored in its `PolygonVertexIndex` array.
+pub(crate) struct Polygons {
+    /// The control point of each polygon vertex.
+    control_points: Vec<u32>,
+    /// The polygon vertices of each polygon.
+    polygons: Vec<Range<usize>>,
+}
+impl Polygons {
+    pub(crate) fn new(polygon_vertices: &[i32]) -> Self {
+        let mut polygons = Vec::new();
+        let mut start = 0;
+        let control_points = polygon_vertices
+            .iter()
+            .enumerate()
+            .map(|(pvi, &vertex)| {
+                // The last vertex of a polygon is stored as `!control_point`.
+                if vertex < 0 {
+                    polygons.push(start..pvi + 1);
+                    start = pvi + 1;
+                    !vertex as u32
+                } else {
+                    vertex as u32
+                }
+            })
+            .collect();
+        Self {
+            control_points,
+            polygons,
+        }
+    }
+
+    /// The control points before and after polygon vertex `pvi` of `polygon`.
+    fn neighbours(&self, polygon: usize, pvi: usize) -> [u32; 2] {
+        let range = &self.polygons[polygon];
+        let previous = if pvi == range.start {
+            range.end - 1
+        } else {
+            pvi - 1
+        };
+        let next = if pvi + 1 == range.end {
+            range.start
+        } else {
+            pvi + 1
+        };
+        [self.control_points[previous], self.control_points[next]]
+    }
+}
+
+/// The smoothing information of a mesh, from its `LayerElementSmoothing`.
+pub(crate) enum Smoothing {
+    /// The smoothing groups of each polygon, as a bit set.
+    ///
+    /// Polygons are smoothed together if they have a group in common.
+    Groups(Vec<i32>),
+    /// The edges which are not smoothed, as pairs of control points.
+    HardEdges(HashSet<(u32, u32)>),
+}
+impl Smoothing {
+    /// Read the smoothing layer element `layer` of `geometry`.
+    pub(crate) fn new(
+        layer: NodeHandle,
+        geometry: NodeHandle,
+        polygons: &Polygons,
+    ) -> anyhow::Result<Self> {
+        let values = child_i32_array(layer, "Smoothing")?
+            .ok_or_else(|| anyhow!("`LayerElementSmoothing` has no `Smoothing`"))?;
+        let indices = match child_string(layer, "ReferenceInformationType")? {
+            None | Some("Direct") => None,
+            // `Index` is the name of `IndexToDirect` in older files.
+            Some("IndexToDirect" | "Index") => Some(
+                child_i32_array(layer, "SmoothingIndex")?
+                    .ok_or_else(|| anyhow!("`LayerElementSmoothing` has no `SmoothingIndex`"))?,
+            ),
+            Some(mode) => bail!("Unknown smoothing reference mode {mode:?}"),
+        };
+        let smoothing = direct_values(values, indices)?;
+        match child_string(layer, "MappingInformationType")? {
+            Some("ByPolygon") => Ok(Self::Groups(smoothing)),
+            Some("ByEdge") => {
+                // Each edge is stored as the polygon vertex it starts from.
+                let edges = child_i32_array(geometry, "Edges")?
+                    .ok_or_else(|| anyhow!("Mesh with edge smoothing has no `Edges`"))?;
+                let polygon_of = polygons
+                    .polygons
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(polygon, range)| range.clone().map(move |pvi| (pvi, polygon)));
+                let polygon_of: HashMap<_, _> = polygon_of.collect();
+                let mut hard_edges = HashSet::new();
+                for (&pvi, &smooth) in edges.iter().zip(&smoothing) {
+                    let pvi = pvi as usize;
+                    if smooth != 0 {
+                        continue;
+                    }
+                    let polygon = *polygon_of
+                        .get(&pvi)
+                        .ok_or_else(|| anyhow!("Edge polygon vertex out of range: {pvi}"))?;
+                    let [_, next] = polygons.neighbours(polygon, pvi);
+                    hard_edges.insert(edge(polygons.control_points[pvi], next));
+                }
+                Ok(Self::HardEdges(hard_edges))
+            }
+            mode => bail!("Unsupported smoothing mapping mode: {mode:?}"),
+        }
+    }
+}
+
+/// The value of each item of a layer element, from its `values`
+/// and the `indices` of the values if it is `IndexToDirect`.
+fn direct_values(values: &[i32], indices: Option<&[i32]>) -> anyhow::Result<Vec<i32>> {
+    let Some(indices) = indices else {
+        return Ok(values.to_vec());
+    };
+    indices
+        .iter()
+        .map(|&index| {
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| values.get(index).copied())
+                .ok_or_else(|| anyhow!("Smoothing index out of range: {index}"))
+        })
+        .collect()
+}
+
+fn edge(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
+
+/// Compute the normal of each polygon vertex of `polygons`.
+///
+/// With [`FbxNormalGeneration::Smooth`], a polygon vertex normal is the average
+/// of the normals of the polygons around its control point that are connected
+/// to its polygon through soft edges, weighted by their area.
+pub(crate) fn generate(
+    points: &[DVec3],
+    polygons: &Polygons,
+    smoothing: Option<&Smoothing>,
+    generation: FbxNormalGeneration,
+) -> anyhow::Result<Vec<DVec3>> {
+    let point = |pvi: usize| {
+        let cp = polygons.control_points[pvi];
+        points
+            .get(cp as usize)
+            .copied()
+            .ok_or_else(|| anyhow!("Control point index out of range: {cp}"))
+    };
+    // Newell's method, the length of the normal is twice the area of the polygon.
+    let face_normals = polygons
+        .polygons
+        .iter()
+        .map(|range| {
+            let mut normal = DVec3::ZERO;
+            for pvi in range.clone() {
+                let next = if pvi + 1 == range.end {
+                    range.start
+                } else {
+                    pvi + 1
+                };
+                normal += point(pvi)?.cross(point(next)?);
+            }
+            Ok(normal)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut normals = vec![DVec3::ZERO; polygons.control_points.len()];
+    let max_angle = match generation {
+        FbxNormalGeneration::Flat => {
+            for (range, normal) in polygons.polygons.iter().zip(&face_normals) {
+                normals[range.clone()].fill(normal.normalize_or_zero());
+            }
+            return Ok(normals);
+        }
+        FbxNormalGeneration::Smooth { max_angle } => max_angle,
+    };
+    let min_cos = f64::from(max_angle).to_radians().cos();
+
+    // The polygon vertices at each control point.
+    let mut corners: HashMap<u32, Vec<(usize, usize)>> = HashMap::new();
+    for (polygon, range) in polygons.polygons.iter().enumerate() {
+        for pvi in range.clone() {
+            corners
+                .entry(polygons.control_points[pvi])
+                .or_default()
+                .push((polygon, pvi));
+        }
+    }
+    let can_smooth = |cp: u32, (p, p_pvi): (usize, usize), (q, q_pvi): (usize, usize)| {
+        let (p_normal, q_normal) = (
+            face_normals[p].normalize_or_zero(),
+            face_normals[q].normalize_or_zero(),
+        );
+        if p_normal.dot(q_normal) < min_cos - 1e-6 {
+            return false;
+        }
+        let q_neighbours = polygons.neighbours(q, q_pvi);
+        let mut shared_edges = polygons
+            .neighbours(p, p_pvi)
+            .into_iter()
+            .filter(|neighbour| q_neighbours.contains(neighbour));
+        match smoothing {
+            None => shared_edges.next().is_some(),
+            Some(Smoothing::Groups(groups)) => {
+                let group = |polygon: usize| groups.get(polygon).copied().unwrap_or(0);
+                group(p) & group(q) != 0 && shared_edges.next().is_some()
+            }
+            Some(Smoothing::HardEdges(hard_edges)) => {
+                shared_edges.any(|neighbour| !hard_edges.contains(&edge(cp, neighbour)))
+            }
+        }
+    };
+    for (&cp, corners) in &corners {
+        // Union-find of the polygons around `cp` that are smoothed together.
+        let mut sets: Vec<_> = (0..corners.len()).collect();
+        fn root(sets: &mut [usize], mut i: usize) -> usize {
+            while sets[i] != i {
+                sets[i] = sets[sets[i]];
+                i = sets[i];
+            }
+            i
+        }
+        for i in 0..corners.len() {
+            for j in i + 1..corners.len() {
+                if can_smooth(cp, corners[i], corners[j]) {
+                    let (i, j) = (root(&mut sets, i), root(&mut sets, j));
+                    sets[i] = j;
+                }
+            }
+        }
+        let mut set_normals = vec![DVec3::ZERO; corners.len()];
+        for (i, &(polygon, _)) in corners.iter().enumerate() {
+            set_normals[root(&mut sets, i)] += face_normals[polygon];
+        }
+        for (i, &(_, pvi)) in corners.iter().enumerate() {
+            normals[pvi] = set_normals[root(&mut sets, i)].normalize_or_zero();
+        }
+    }
+    Ok(normals)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use bevy::{math::DVec3, utils::HashSet};
+
+    use super::{direct_values, edge, generate, Polygons, Smoothing};
+    use crate::loader::FbxNormalGeneration;
+
+    /// Two quads sharing the edge between control points 1 and 2 at a right angle,
+    /// facing +Z and +X.
+    fn corner() -> (Vec<DVec3>, Polygons) {
+        let points = vec![
+            DVec3::new(0.0, 0.0, 0.0),
+            DVec3::new(1.0, 0.0, 0.0),
+            DVec3::new(1.0, 1.0, 0.0),
+            DVec3::new(0.0, 1.0, 0.0),
+            DVec3::new(1.0, 0.0, -1.0),
+            DVec3::new(1.0, 1.0, -1.0),
+        ];
+        let polygons = Polygons::new(&[0, 1, 2, !3, 1, 4, 5, !2]);
+        (points, polygons)
+    }
+
+    const SMOOTH: FbxNormalGeneration = FbxNormalGeneration::Smooth { max_angle: 90.0 };
+    /// The polygon vertices of the shared edge, in the first and second quad.
+    const SHARED: [usize; 4] = [1, 2, 4, 7];
+
+    fn assert_normals(normals: &[DVec3], pvis: &[usize], expected: DVec3) {
+        for &pvi in pvis {
+            assert!(
+                normals[pvi].abs_diff_eq(expected, 1e-9),
+                "normal of {pvi} is {}, expected {expected}",
+                normals[pvi]
+            );
+        }
+    }
+
+    /// Check that the quads are smoothed along the shared edge only.
+    fn assert_smoothed(normals: &[DVec3]) {
+        let average = DVec3::new(FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2);
+        assert_normals(normals, &SHARED, average);
+        assert_normals(normals, &[0, 3], DVec3::Z);
+        assert_normals(normals, &[5, 6], DVec3::X);
+    }
+
+    /// Check that each quad has its face normal.
+    fn assert_split(normals: &[DVec3]) {
+        assert_normals(normals, &[0, 1, 2, 3], DVec3::Z);
+        assert_normals(normals, &[4, 5, 6, 7], DVec3::X);
+    }
+
+    #[test]
+    fn flat_normals_are_face_normals() {
+        let (points, polygons) = corner();
+        let normals = generate(&points, &polygons, None, FbxNormalGeneration::Flat).unwrap();
+        assert_split(&normals);
+    }
+
+    #[test]
+    fn smooth_normals_average_neighbours() {
+        let (points, polygons) = corner();
+        assert_smoothed(&generate(&points, &polygons, None, SMOOTH).unwrap());
+    }
+
+    #[test]
+    fn max_angle_splits_normals() {
+        let (points, polygons) = corner();
+        let generation = FbxNormalGeneration::Smooth { max_angle: 60.0 };
+        assert_split(&generate(&points, &polygons, None, generation).unwrap());
+    }
+
+    #[test]
+    fn smoothing_groups_split_normals() {
+        let (points, polygons) = corner();
+        let separate = Smoothing::Groups(vec![0b01, 0b10]);
+        assert_split(&generate(&points, &polygons, Some(&separate), SMOOTH).unwrap());
+        let shared = Smoothing::Groups(vec![0b01, 0b11]);
+        assert_smoothed(&generate(&points, &polygons, Some(&shared), SMOOTH).unwrap());
+    }
+
+    #[test]
+    fn hard_edges_split_normals() {
+        let (points, polygons) = corner();
+        let hard = Smoothing::HardEdges(HashSet::from([edge(2, 1)]));
+        assert_split(&generate(&points, &polygons, Some(&hard), SMOOTH).unwrap());
+        // A hard edge that isn't shared by the quads doesn't split them.
+        let border = Smoothing::HardEdges(HashSet::from([edge(0, 1)]));
+        assert_smoothed(&generate(&points, &polygons, Some(&border), SMOOTH).unwrap());
+    }
+
+    #[test]
+    fn smoothing_values_are_indexed() {
+        assert_eq!(direct_values(&[1, 2], None).unwrap(), [1, 2]);
+        assert_eq!(direct_values(&[1, 2], Some(&[1, 1, 0])).unwrap(), [2, 2, 1]);
+        assert!(direct_values(&[1, 2], Some(&[2])).is_err());
+        assert!(direct_values(&[1, 2], Some(&[-1])).is_err());
+    }
+}
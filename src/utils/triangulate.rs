@@ -1,40 +1,15 @@
 //! Triangulator.
 
-// TODO: https://github.com/HeavyRain266/bevy_mod_fbx/issues/11
-
 use anyhow::{anyhow, bail};
 use bevy::math::{DVec2, DVec3};
 use fbxcel_dom::v7400::data::mesh::{PolygonVertexIndex, PolygonVertices};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Axis {
-    X,
-    Y,
-    Z,
-}
-
-/// Returns smallest direction.
-fn smallest_direction(v: &DVec3) -> Axis {
-    match () {
-        () if v.x < v.y && v.z < v.x => Axis::Z,
-        () if v.x < v.y => Axis::X,
-        () if v.z < v.y => Axis::Z,
-        () => Axis::Y,
-    }
-}
-
 /// Triangulate.
 pub fn triangulate(
     pvs: &PolygonVertices<'_>,
     poly_pvis: &[PolygonVertexIndex],
     results: &mut Vec<[PolygonVertexIndex; 3]>,
 ) -> anyhow::Result<()> {
-    macro_rules! get_vec {
-        ($pvii:expr) => {
-            get_vec(pvs, poly_pvis[$pvii])
-        };
-    }
-
     match poly_pvis.len() {
         n @ 0..=2 => {
             // Not a polygon.
@@ -47,115 +22,105 @@ pub fn triangulate(
 
             Ok(())
         }
-        4 => {
-            // p0, p1, p2, p3: vertices of the quadrangle (angle{0..3}).
-            let p0 = get_vec!(0)?;
-            let p1 = get_vec!(1)?;
-            let p2 = get_vec!(2)?;
-            let p3 = get_vec!(3)?;
-
-            // n1: Normal vector calculated with two edges of the angle1.
-            // n3: Normal vector calculated with two edges of the angle3.
-            let n1 = (p0 - p1).cross(p1 - p2);
-            let n3 = (p2 - p3).cross(p3 - p0);
-
-            // If both angle1 and angle3 are concave, vectors n1 and n3 are
-            // oriented in the same direction and `n1.dot(n3)` will be positive.
-            // If either angle1 or angle3 is concave, vector n1 and n3 are
-            // oriented in the opposite directions and `n1.dot(n3)` will be
-            // negative.
-            // It does not matter when the vertices of quadrangle is not on the
-            // same plane, because whichever diagonal you choose, the cut will
-            // be inaccurate.
-            if n1.dot(n3) >= 0.0 {
-                // Both angle1 and angle3 are concave.
-                // This means that either angle0 or angle2 can be convex.
-                // Cut from p0 to p2.
-                results.extend_from_slice(&[
-                    [poly_pvis[0], poly_pvis[1], poly_pvis[2]],
-                    [poly_pvis[2], poly_pvis[3], poly_pvis[0]],
-                ]);
-            } else {
-                // Either angle1 or angle3 is convex.
-                // Cut from p1 to p3.
-                results.extend_from_slice(&[
-                    [poly_pvis[0], poly_pvis[1], poly_pvis[3]],
-                    [poly_pvis[3], poly_pvis[1], poly_pvis[2]],
-                ]);
-            }
+        _ => {
+            let points = poly_pvis
+                .iter()
+                .map(|&pvi| get_vec(pvs, pvi))
+                .collect::<Result<Vec<_>, _>>()?;
+            let triangles = ear_clip(&points);
+            results.extend(
+                triangles
+                    .into_iter()
+                    .map(|triangle| triangle.map(|i| poly_pvis[i])),
+            );
+
             Ok(())
         }
-        n => {
-            let points = (0..n).map(|i| get_vec!(i)).collect::<Result<Vec<_>, _>>()?;
-            let points_2d: Vec<_> = {
-                // Reduce dimensions for faster computation.
-                // This helps treat points which are not on a single plane.
-                let (min, max) =
-                    bounding_box(&points).expect("Should never happen: there are 5 or more points");
-
-                let width = max - min;
-
-                match smallest_direction(&width) {
-                    Axis::X => points.into_iter().map(|v| DVec2::new(v[1], v[2])).collect(),
-                    Axis::Y => points.into_iter().map(|v| DVec2::new(v[0], v[2])).collect(),
-                    Axis::Z => points.into_iter().map(|v| DVec2::new(v[0], v[1])).collect(),
-                }
-            };
-            // Normal directions.
-            let normal_directions = {
-                // 0 ... n-1
-                let iter_cur = points_2d.iter();
-
-                // n-1, 0, ... n-2
-                let iter_prev = points_2d.iter().cycle().skip(n - 1);
-
-                // 1, ... n-1, 0
-                let iter_next = points_2d.iter().cycle().skip(1);
-
-                iter_cur
-                    .zip(iter_prev)
-                    .zip(iter_next)
-                    .map(|((cur, prev), next)| {
-                        let prev_cur = *prev - *cur;
-                        let cur_next = *cur - *next;
-                        prev_cur.perp_dot(cur_next) > 0.0
-                    })
-                    .collect::<Vec<_>>()
-            };
-            assert_eq!(normal_directions.len(), n);
-
-            let dirs_true_count = normal_directions.iter().filter(|&&v| v).count();
-
-            if dirs_true_count <= 1 || dirs_true_count >= n - 1 {
-                // Zero or one angles are concave.
-                let minor_sign = dirs_true_count <= 1;
-
-                // If there are no concave angles, use 0 as center.
-                let convex_index = normal_directions
-                    .iter()
-                    .position(|&sign| sign == minor_sign)
-                    .unwrap_or(0);
-
-                let convex_pvi = poly_pvis[convex_index];
-
-                let iter1 = (0..n)
-                    .cycle()
-                    .skip(convex_index + 1)
-                    .take(n - 2)
-                    .map(|i| poly_pvis[i]);
-
-                let iter2 = (0..n).cycle().skip(convex_index + 2).map(|i| poly_pvis[i]);
-
-                for (pvi1, pvi2) in iter1.zip(iter2) {
-                    results.push([convex_pvi, pvi1, pvi2]);
-                }
+    }
+}
 
-                Ok(())
-            } else {
-                bail!("Unsupported polygon: {n}-gon with two or more concave angles");
-            }
-        }
+/// Triangulate the polygon `points` by ear clipping.
+///
+/// The polygon is projected on its plane, and may be concave or have collinear
+/// or duplicate points. The triangles, as indices in `points`, have the same
+/// winding as the polygon.
+///
+/// Degenerate polygons, such as self-intersecting ones, still get `n - 2`
+/// triangles, but they may overlap.
+fn ear_clip(points: &[DVec3]) -> Vec<[usize; 3]> {
+    let points = project(points);
+    let epsilon = {
+        let (min, max) = points.iter().fold(
+            (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY)),
+            |(min, max), &point| (min.min(point), max.max(point)),
+        );
+        let size = (max - min).max_element();
+        size * size * 1e-12
+    };
+    // Twice the signed area of the triangle, positive if counter-clockwise.
+    let area = |[a, b, c]: [DVec2; 3]| (b - a).perp_dot(c - a);
+
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::with_capacity(points.len().saturating_sub(2));
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let corner = |i: usize| {
+            [
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            ]
+        };
+        let corner_area = |i: usize| area(corner(i).map(|j| points[j]));
+        let is_ear = |i: usize| {
+            let [a, b, c] = corner(i).map(|j| points[j]);
+            // No other point may be inside the ear, or on its edges.
+            corner_area(i) > epsilon
+                && remaining.iter().all(|&j| {
+                    let point = points[j];
+                    let is_corner = point == a || point == b || point == c;
+                    let is_inside = area([a, b, point]) >= -epsilon
+                        && area([b, c, point]) >= -epsilon
+                        && area([c, a, point]) >= -epsilon;
+                    is_corner || !is_inside
+                })
+        };
+        // Without an ear, clip a flat corner, which doesn't change the shape of
+        // the polygon, or as a last resort the most convex corner.
+        let ear = (0..n)
+            .find(|&i| is_ear(i))
+            .or_else(|| (0..n).find(|&i| corner_area(i).abs() <= epsilon))
+            .unwrap_or_else(|| {
+                (0..n)
+                    .max_by(|&i, &j| corner_area(i).total_cmp(&corner_area(j)))
+                    .unwrap_or(0)
+            });
+        triangles.push(corner(ear));
+        remaining.remove(ear);
+    }
+    if let [a, b, c] = remaining[..] {
+        triangles.push([a, b, c]);
     }
+    triangles
+}
+
+/// Project `points` on the plane of the polygon they form, so that
+/// it is counter-clockwise.
+fn project(points: &[DVec3]) -> Vec<DVec2> {
+    // Newell's method, robust to concave and slightly non-planar polygons.
+    let normal = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .fold(DVec3::ZERO, |normal, (current, next)| {
+            normal + current.cross(*next)
+        });
+    let normal = normal.try_normalize().unwrap_or(DVec3::Z);
+    let u = normal.any_orthonormal_vector();
+    let v = normal.cross(u);
+    points
+        .iter()
+        .map(|point| DVec2::new(point.dot(u), point.dot(v)))
+        .collect()
 }
 
 /// Returns the vector.
@@ -165,17 +130,219 @@ fn get_vec(pvs: &PolygonVertices<'_>, pvi: PolygonVertexIndex) -> anyhow::Result
         .ok_or_else(|| anyhow!("Index out of range: {pvi:?}"))
 }
 
-/// Returns bounding box as `(min, max)`.
-fn bounding_box<'a>(points: impl IntoIterator<Item = &'a DVec3>) -> Option<(DVec3, DVec3)> {
-    points.into_iter().fold(None, |minmax, point| {
-        minmax.map_or_else(
-            || Some((*point, *point)),
-            |(min, max)| {
-                Some((
-                    DVec3::new(min.x.min(point.x), min.y.min(point.y), min.z.min(point.z)),
-                    DVec3::new(max.x.max(point.x), max.y.max(point.y), max.z.max(point.z)),
-                ))
-            },
-        )
-    })
+#[cfg(test)]
+mod tests {
+    use bevy::math::{DQuat, DVec2, DVec3};
+
+    use super::ear_clip;
+
+    fn polygon(points: &[(f64, f64)]) -> Vec<DVec3> {
+        points.iter().map(|&(x, y)| DVec3::new(x, y, 0.0)).collect()
+    }
+
+    /// Twice the signed area of `points` around `normal`.
+    fn area(points: &[DVec3], normal: DVec3) -> f64 {
+        let cross = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .fold(DVec3::ZERO, |sum, (current, next)| {
+                sum + current.cross(*next)
+            });
+        cross.dot(normal)
+    }
+
+    fn is_inside(points: &[DVec3], point: DVec2) -> bool {
+        let mut inside = false;
+        for (a, b) in points.iter().zip(points.iter().cycle().skip(1)) {
+            if (a.y > point.y) != (b.y > point.y) {
+                let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if point.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    /// Check that the triangulation of a planar polygon covers it exactly,
+    /// with triangles of the same winding as the polygon.
+    fn check(points: &[DVec3], normal: DVec3) -> Vec<[usize; 3]> {
+        let triangles = ear_clip(points);
+        assert_eq!(triangles.len(), points.len() - 2);
+
+        let polygon_area = area(points, normal);
+        let mut total_area = 0.0;
+        for triangle in &triangles {
+            let corners = triangle.map(|i| points[i]);
+            let triangle_area = area(&corners, normal);
+            assert!(
+                triangle_area * polygon_area.signum() >= -1e-9,
+                "flipped triangle {triangle:?} in {triangles:?}"
+            );
+            total_area += triangle_area;
+        }
+        assert!(
+            (total_area - polygon_area).abs() < 1e-9,
+            "triangles cover {total_area}, polygon is {polygon_area}: {triangles:?}"
+        );
+        triangles
+    }
+
+    /// Also check that the triangles are inside the polygon, for polygons in the XY plane.
+    fn check_xy(points: &[DVec3]) -> Vec<[usize; 3]> {
+        let triangles = check(points, DVec3::Z);
+        for triangle in &triangles {
+            let corners = triangle.map(|i| points[i]);
+            if area(&corners, DVec3::Z).abs() > 1e-9 {
+                let centroid = (corners[0] + corners[1] + corners[2]) / 3.0;
+                assert!(
+                    is_inside(points, centroid.truncate()),
+                    "triangle {triangle:?} is outside the polygon"
+                );
+            }
+        }
+        triangles
+    }
+
+    #[test]
+    fn square() {
+        check_xy(&polygon(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]));
+    }
+
+    #[test]
+    fn concave_quad() {
+        // Arrow head, the only valid diagonal goes through the concave corner.
+        check_xy(&polygon(&[(0.0, 0.0), (2.0, 1.0), (0.0, 2.0), (1.0, 1.0)]));
+    }
+
+    #[test]
+    fn l_shape() {
+        check_xy(&polygon(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 2.0),
+            (0.0, 2.0),
+        ]));
+    }
+
+    #[test]
+    fn star() {
+        let points: Vec<_> = (0..10)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::TAU / 10.0;
+                let radius = if i % 2 == 0 { 2.0 } else { 0.8 };
+                (radius * angle.cos(), radius * angle.sin())
+            })
+            .collect();
+        check_xy(&polygon(&points));
+    }
+
+    #[test]
+    fn comb() {
+        check_xy(&polygon(&[
+            (0.0, 0.0),
+            (5.0, 0.0),
+            (5.0, 3.0),
+            (4.0, 3.0),
+            (4.0, 1.0),
+            (3.0, 1.0),
+            (3.0, 3.0),
+            (2.0, 3.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ]));
+    }
+
+    #[test]
+    fn spiral() {
+        check_xy(&polygon(&[
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 4.0),
+            (1.0, 4.0),
+            (1.0, 2.0),
+            (2.0, 2.0),
+            (2.0, 3.0),
+            (3.0, 3.0),
+            (3.0, 1.0),
+            (0.0, 1.0),
+        ]));
+    }
+
+    #[test]
+    fn collinear_points() {
+        check_xy(&polygon(&[
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (3.0, 0.0),
+            (3.0, 1.0),
+            (3.0, 2.0),
+            (1.5, 2.0),
+            (0.0, 2.0),
+            (0.0, 1.0),
+        ]));
+    }
+
+    #[test]
+    fn duplicate_points() {
+        check_xy(&polygon(&[
+            (0.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 0.0),
+            (1.0, 1.0),
+            (0.0, 1.0),
+            (0.0, 0.0),
+        ]));
+    }
+
+    #[test]
+    fn clockwise() {
+        let mut points = polygon(&[
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 2.0),
+            (0.0, 2.0),
+        ]);
+        points.reverse();
+        check_xy(&points);
+    }
+
+    #[test]
+    fn tilted_plane() {
+        let rotation = DQuat::from_euler(bevy::math::EulerRot::XYZ, 0.7, -1.2, 0.3);
+        let points: Vec<_> = polygon(&[
+            (0.0, 0.0),
+            (5.0, 0.0),
+            (5.0, 3.0),
+            (4.0, 3.0),
+            (4.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 3.0),
+            (0.0, 3.0),
+        ])
+        .into_iter()
+        .map(|point| rotation * point + DVec3::new(10.0, -3.0, 7.0))
+        .collect();
+        check(&points, rotation * DVec3::Z);
+    }
+
+    #[test]
+    fn all_collinear() {
+        let points = polygon(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (1.5, 0.0)]);
+        assert_eq!(ear_clip(&points).len(), 3);
+    }
+
+    #[test]
+    fn self_intersecting() {
+        // Bow tie, there is no valid triangulation but it must not fail.
+        let points = polygon(&[(0.0, 0.0), (2.0, 2.0), (2.0, 0.0), (0.0, 2.0)]);
+        assert_eq!(ear_clip(&points).len(), 2);
+    }
 }
@@ -0,0 +1,268 @@
+//! Vertex buffer operations for the meshes built by the loader.
+
+use bevy::{
+    math::{Mat3, Mat4, Vec3},
+    prelude::Mesh,
+    render::mesh::{Indices, VertexAttributeValues},
+    utils::HashMap,
+};
+
+/// Transform the positions, normals and tangents of `mesh` by `matrix`.
+///
+/// This doesn't flip the triangles when `matrix` is a mirroring,
+/// but the handedness of the tangents is kept.
+pub(crate) fn transform(mesh: &mut Mesh, matrix: Mat4) {
+    let linear = Mat3::from_mat4(matrix);
+    let normal_matrix = linear.inverse().transpose();
+    let handedness = linear.determinant().signum();
+    if let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+    {
+        for position in positions {
+            *position = matrix.transform_point3(Vec3::from(*position)).to_array();
+        }
+    }
+    if let Some(VertexAttributeValues::Float32x3(normals)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL)
+    {
+        for normal in normals {
+            *normal = (normal_matrix * Vec3::from(*normal))
+                .normalize_or_zero()
+                .to_array();
+        }
+    }
+    if let Some(VertexAttributeValues::Float32x4(tangents)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_TANGENT)
+    {
+        for tangent in tangents {
+            let [x, y, z, w] = *tangent;
+            let xyz = (linear * Vec3::new(x, y, z)).normalize_or_zero();
+            *tangent = xyz.extend(w * handedness).to_array();
+        }
+    }
+}
+
+/// Merge the identical vertices of `mesh`, which must not have indices yet.
+///
+/// Vertices are identical if all their attributes are bitwise equal,
+/// and, if `distinct` is set, their values in `distinct` are the same.
+///
+/// Returns the index of the merged vertex of each original vertex.
+pub(crate) fn weld(mesh: &mut Mesh, distinct: Option<&[u32]>) -> Vec<u32> {
+    let count = mesh.count_vertices();
+    if count == 0 {
+        return Vec::new();
+    }
+    let attributes: Vec<_> = mesh
+        .attributes()
+        .map(|(_, values)| values.get_bytes())
+        .collect();
+    let mut keys = Vec::new();
+    for i in 0..count {
+        for bytes in &attributes {
+            let size = bytes.len() / count;
+            keys.extend_from_slice(&bytes[i * size..(i + 1) * size]);
+        }
+        if let Some(distinct) = distinct {
+            keys.extend_from_slice(&distinct[i].to_le_bytes());
+        }
+    }
+    let stride = keys.len() / count;
+
+    let mut welded = HashMap::new();
+    let mut kept = Vec::new();
+    let remap = keys
+        .chunks_exact(stride)
+        .enumerate()
+        .map(|(i, key)| {
+            *welded.entry(key).or_insert_with(|| {
+                kept.push(i as u32);
+                kept.len() as u32 - 1
+            })
+        })
+        .collect();
+    // `duplicate_vertices` picks the vertices in the index buffer, which
+    // here are the first of each group of identical vertices.
+    mesh.insert_indices(Indices::U32(kept));
+    mesh.duplicate_vertices();
+    remap
+}
+
+/// A copy of `mesh` with only the vertices used by `indices`, indexed by `indices`.
+///
+/// Also returns the index in `mesh` of each vertex of the copy.
+pub(crate) fn compact(mesh: &Mesh, indices: &[u32]) -> (Mesh, Vec<u32>) {
+    let mut new_indices = vec![u32::MAX; mesh.count_vertices()];
+    let mut kept = Vec::new();
+    let indices: Vec<_> = indices
+        .iter()
+        .map(|&i| {
+            let new_index = &mut new_indices[i as usize];
+            if *new_index == u32::MAX {
+                *new_index = kept.len() as u32;
+                kept.push(i);
+            }
+            *new_index
+        })
+        .collect();
+    let mut compacted = mesh.clone();
+    compacted.insert_indices(Indices::U32(kept.clone()));
+    compacted.duplicate_vertices();
+    compacted.insert_indices(self::indices(indices, kept.len()));
+    (compacted, kept)
+}
+
+/// Use the smallest index format that can address `vertex_count` vertices.
+pub(crate) fn indices(indices: Vec<u32>, vertex_count: usize) -> Indices {
+    if vertex_count <= u16::MAX as usize {
+        Indices::U16(indices.into_iter().map(|i| i as u16).collect())
+    } else {
+        Indices::U32(indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::RenderAssetUsages,
+        prelude::Mesh,
+        render::mesh::{Indices, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues},
+    };
+
+    use super::{compact, weld};
+
+    fn mesh(positions: &[[f32; 3]], normals: &[[f32; 3]]) -> Mesh {
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions.to_vec())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals.to_vec())
+    }
+
+    fn float3(mesh: &Mesh, attribute: MeshVertexAttribute) -> Vec<[f32; 3]> {
+        match mesh.attribute(attribute) {
+            Some(VertexAttributeValues::Float32x3(values)) => values.clone(),
+            values => panic!("unexpected {} values: {values:?}", attribute.name),
+        }
+    }
+
+    /// Check that each original vertex is remapped to a vertex with the same attributes.
+    fn check_remap(original: &Mesh, welded: &Mesh, remap: &[u32]) {
+        assert_eq!(remap.len(), original.count_vertices());
+        for attribute in [Mesh::ATTRIBUTE_POSITION, Mesh::ATTRIBUTE_NORMAL] {
+            let original = float3(original, attribute);
+            let welded = float3(welded, attribute);
+            for (i, &new_index) in remap.iter().enumerate() {
+                assert_eq!(original[i], welded[new_index as usize], "vertex {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn merges_vertices_with_identical_attributes() {
+        let a = [0.0, 0.0, 0.0];
+        let b = [1.0, 0.0, 0.0];
+        let up = [0.0, 1.0, 0.0];
+        let front = [0.0, 0.0, 1.0];
+        let original = mesh(&[a, b, a, a, b], &[up, up, up, front, up]);
+        let mut welded = original.clone();
+        let remap = weld(&mut welded, None);
+
+        assert_eq!(remap, [0, 1, 0, 2, 1]);
+        assert_eq!(welded.count_vertices(), 3);
+        assert!(welded.indices().is_none());
+        assert_eq!(float3(&welded, Mesh::ATTRIBUTE_POSITION), [a, b, a]);
+        assert_eq!(float3(&welded, Mesh::ATTRIBUTE_NORMAL), [up, up, front]);
+        check_remap(&original, &welded, &remap);
+    }
+
+    #[test]
+    fn keeps_distinct_vertices_apart() {
+        let a = [0.0, 0.0, 0.0];
+        let up = [0.0, 1.0, 0.0];
+        let original = mesh(&[a, a, a, a], &[up, up, up, up]);
+        let mut welded = original.clone();
+        // Such as the corners of different control points, which may be deformed differently.
+        let remap = weld(&mut welded, Some(&[0, 1, 0, 1]));
+
+        assert_eq!(remap, [0, 1, 0, 1]);
+        assert_eq!(welded.count_vertices(), 2);
+        check_remap(&original, &welded, &remap);
+    }
+
+    #[test]
+    fn empty_mesh_is_unchanged() {
+        let mut welded = mesh(&[], &[]);
+        assert!(weld(&mut welded, None).is_empty());
+        assert_eq!(welded.count_vertices(), 0);
+    }
+
+    fn skinned_mesh(vertex_count: usize) -> Mesh {
+        let positions: Vec<_> = (0..vertex_count).map(|i| [i as f32, 0.0, 0.0]).collect();
+        let normals = vec![[0.0, 1.0, 0.0]; vertex_count];
+        let joints: Vec<_> = (0..vertex_count)
+            .map(|i| [i as u16, (i / 2) as u16, 0, 0])
+            .collect();
+        let weights: Vec<_> = (0..vertex_count)
+            .map(|i| [i as f32, 1.0, 0.0, 0.0])
+            .collect();
+        mesh(&positions, &normals)
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_JOINT_INDEX,
+                VertexAttributeValues::Uint16x4(joints),
+            )
+            .with_inserted_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, weights)
+    }
+
+    #[test]
+    fn compact_drops_unused_vertices() {
+        let original = skinned_mesh(6);
+        let (compacted, kept) = compact(&original, &[4, 1, 5, 5, 1, 4]);
+
+        // The vertices are kept in the order of their first use.
+        assert_eq!(kept, [4, 1, 5]);
+        assert_eq!(compacted.count_vertices(), 3);
+        match compacted.indices() {
+            Some(Indices::U16(indices)) => assert_eq!(indices, &[0, 1, 2, 2, 1, 0]),
+            indices => panic!("expected u16 indices, got {indices:?}"),
+        }
+        // The morph targets are built from the original vertices of `kept`,
+        // so every attribute must follow the same order.
+        for (attribute, values) in compacted.attributes() {
+            let original = original.attribute(attribute.id).unwrap().get_bytes();
+            let size = values.get_bytes().len() / kept.len();
+            for (i, &vertex) in kept.iter().enumerate() {
+                let vertex = vertex as usize;
+                assert_eq!(
+                    values.get_bytes()[i * size..(i + 1) * size],
+                    original[vertex * size..(vertex + 1) * size],
+                    "vertex {i} of {}",
+                    attribute.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compact_uses_u16_indices_when_possible() {
+        let count = u16::MAX as usize;
+        let indices: Vec<_> = (0..count as u32).collect();
+        let (compacted, _) = compact(&skinned_mesh(count), &indices);
+        assert!(matches!(compacted.indices(), Some(Indices::U16(_))));
+    }
+
+    #[test]
+    fn compact_uses_u32_indices_above_u16_range() {
+        let count = u16::MAX as usize + 1;
+        let indices: Vec<_> = (0..count as u32).rev().collect();
+        let (compacted, kept) = compact(&skinned_mesh(count + 1), &indices);
+        assert_eq!(kept, indices);
+        match compacted.indices() {
+            Some(Indices::U32(new_indices)) => {
+                assert!(new_indices.iter().copied().eq(0..count as u32));
+            }
+            indices => panic!("expected u32 indices, got {:?}", indices.map(Indices::len)),
+        }
+    }
+}